@@ -0,0 +1,71 @@
+use std::io::{self, IoSlice, Write};
+
+/// The number of bytes that a `Batched` writer holds at most.
+const CAPACITY: usize = 8 * 1024;
+
+/// A writer that coalesces the small writes of `Stringify2` impls, e.g.
+/// of separators and indentation units, into fewer and larger writes to
+/// `inner`. A write that doesn't fit in the buffer is passed on together
+/// with what is buffered as a single vectored write. What is buffered is
+/// written to `inner` by `finish` or `flush`, and lost if neither is called.
+pub(crate) struct Batched<'w, W> {
+    inner: &'w mut W,
+    buf: Vec<u8>,
+}
+
+impl<'w, W> Batched<'w, W>
+where W: Write {
+    pub(crate) fn new(inner: &'w mut W) -> Self {
+        Self { inner, buf: Vec::with_capacity(CAPACITY) }
+    }
+
+    /// Write what is buffered to `inner`, without flushing that.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        self.drain()
+    }
+
+    fn drain(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl<W> Write for Batched<'_, W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + bytes.len() > CAPACITY {
+            if bytes.len() >= CAPACITY {
+                write_all_vectored(self.inner, &self.buf, bytes)?;
+                self.buf.clear();
+                return Ok(bytes.len());
+            }
+            self.drain()?;
+        }
+        self.buf.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.drain()?;
+        self.inner.flush()
+    }
+}
+
+/// Write all of `first` followed by all of `second` to `inner`, with as few
+/// calls to `write_vectored` as `inner` allows.
+fn write_all_vectored<W>(inner: &mut W, first: &[u8], second: &[u8]) -> io::Result<()>
+where W: Write {
+    let mut slices = [IoSlice::new(first), IoSlice::new(second)];
+    let mut slices = &mut slices[..];
+    IoSlice::advance_slices(&mut slices, 0);
+    while !slices.is_empty() {
+        match inner.write_vectored(slices) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => IoSlice::advance_slices(&mut slices, written),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
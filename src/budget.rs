@@ -0,0 +1,46 @@
+use std::io::{self, Write};
+
+/// A writer that forwards to `inner` until `max_bytes` have been written.
+/// A write that would exceed the budget is rejected as a whole rather than
+/// partially written, so that multi-byte characters are never split.
+/// After that, every write fails, which makes the stringification that
+/// is writing to it return early.
+pub(crate) struct Budget<'w, W> {
+    inner: &'w mut W,
+    remaining: usize,
+    exhausted: bool,
+}
+
+impl<'w, W> Budget<'w, W>
+where W: Write {
+    pub(crate) fn new(inner: &'w mut W, max_bytes: usize) -> Self {
+        Self { inner, remaining: max_bytes, exhausted: false }
+    }
+
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// The writer wrapped by `self`, which can be written to regardless
+    /// of the budget e.g. to append a truncation notice.
+    pub(crate) fn inner(&mut self) -> &mut W {
+        self.inner
+    }
+}
+
+impl<W> Write for Budget<'_, W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if self.exhausted || bytes.len() > self.remaining {
+            self.exhausted = true;
+            return Err(io::Error::other("output budget exhausted"));
+        }
+        self.inner.write_all(bytes)?;
+        self.remaining -= bytes.len();
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,284 @@
+use crate::options::{Guides, IndentPolicy, Options};
+use crate::styles::Style;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+/// A step from a value to one of its components.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathSegment {
+    /// A named field of a struct or enum variant.
+    Field(String),
+    /// An element of a sequence or tuple, or a positional field.
+    Index(usize),
+    /// The value associated with a stringified key in a map.
+    Key(String),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) => write!(f, ".{}", name),
+            PathSegment::Index(idx) => write!(f, "[{}]", idx),
+            PathSegment::Key(key) => write!(f, "[{}]", key),
+        }
+    }
+}
+
+/// The state of a stringification that is threaded through the recursion:
+/// - the `depth` of the value currently being stringified, which is the
+///   indentation level that `Style`s with `Newline::Add` are relative to
+/// - the `path` of fields, indices and keys leading from the root value
+///   to the value currently being stringified
+/// - the `column` of the output that has been reached, and whether the
+///   current value is written `flat` i.e. on a single line, for
+///   `Layout::Fit`
+/// - the indentation written so far, which `Stringify2::indent` reuses
+///   rather than building it up one unit at a time on every line
+/// - the paths of the values shared by `Rc`s and `Arc`s that have been
+///   written so far, by their address, for `Options::dedup`
+/// - the width that the field names of the current value are padded to
+///   for `Options::align`
+/// - the indentation level of the line that is being written, for the
+///   `LinePrefix` of the `Options`
+/// - whether indentation is being written, so that it can be left out
+///   at the end of the lines of canonical output
+/// - whether anything other than indentation has been written on the
+///   current line, for `Multiline::Margin`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StringifyContext {
+    depth: usize,
+    path: Vec<PathSegment>,
+    column: Rc<Cell<usize>>,
+    flat: bool,
+    paddings: Rc<RefCell<Vec<Padding>>>,
+    shared: Rc<RefCell<HashMap<usize, String>>>,
+    name_width: usize,
+    line_level: Rc<Cell<usize>>,
+    indenting: Rc<Cell<bool>>,
+    line_started: Rc<Cell<bool>>,
+}
+
+/// The indentation of the deepest level written so far with an indentation
+/// policy, unit and guides. That of every other level is a prefix of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Padding {
+    policy: IndentPolicy,
+    unit: Cow<'static, str>,
+    guides: Guides,
+    text: String,
+    /// The length of the indentation of each level, starting at level 1.
+    ends: Vec<usize>,
+}
+
+impl StringifyContext {
+    /// Create a context for stringifying a root value.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// The column of the output that has been reached. It is only tracked
+    /// by `Stringify2::stringify_root` with `Layout::Fit`, and 0 otherwise.
+    pub fn column(&self) -> usize {
+        self.column.get()
+    }
+
+    pub(crate) fn column_cell(&self) -> Rc<Cell<usize>> {
+        self.column.clone()
+    }
+
+    /// The indentation level of the line that is being written, as set
+    /// by `Stringify2::indent` when it starts a new line.
+    pub(crate) fn line_level_cell(&self) -> Rc<Cell<usize>> {
+        self.line_level.clone()
+    }
+
+    /// Whether the bytes that are being written are indentation, as set
+    /// by `Stringify2::indent` while it writes the indentation of a line.
+    pub(crate) fn indenting_cell(&self) -> Rc<Cell<bool>> {
+        self.indenting.clone()
+    }
+
+    /// Whether anything other than indentation has been written on the
+    /// current line. It is only tracked by `Stringify2::stringify_root`,
+    /// and `false` otherwise.
+    pub(crate) fn line_started_cell(&self) -> Rc<Cell<bool>> {
+        self.line_started.clone()
+    }
+
+    /// Return `true` if the current value is written on a single line,
+    /// in which case no line breaks or indentation are written.
+    pub fn is_flat(&self) -> bool {
+        self.flat
+    }
+
+    /// Run `f` with `self` marked as flat (see `is_flat`).
+    pub(crate) fn flat<T, F>(&mut self, f: F) -> T
+    where F: FnOnce(&mut Self) -> T {
+        let flat = std::mem::replace(&mut self.flat, true);
+        let result = f(self);
+        self.flat = flat;
+        result
+    }
+
+    /// A context for stringifying the value at `path` and `depth` of a root
+    /// value, e.g. on another thread than the one that stringifies the root
+    /// value. The depth isn't that of the path, since components that are
+    /// written inline (see `StringifyContext::inline`) add to the path only.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn at(depth: usize, path: Vec<PathSegment>) -> Self {
+        Self { depth, path, ..Self::default() }
+    }
+
+    /// Run `f` with the indentation of `levels` levels written with the
+    /// `IndentPolicy` and `Guides` of `options`, with the unit of `style`.
+    pub(crate) fn padding<T, F>(&self, options: &Options, style: &Style, levels: usize, f: F) -> T
+    where F: FnOnce(&str) -> T {
+        if levels == 0 {
+            return f("");
+        }
+        let mut paddings = self.paddings.borrow_mut();
+        let idx = paddings.iter().position(|padding| {
+            padding.unit == style.indent && padding.guides == options.guides && padding.policy == options.indent
+        });
+        let idx = match idx {
+            Some(idx) => idx,
+            None => {
+                paddings.push(Padding {
+                    policy: options.indent.clone(),
+                    unit: style.indent.clone(),
+                    guides: options.guides,
+                    text: String::new(),
+                    ends: vec![],
+                });
+                paddings.len() - 1
+            },
+        };
+        let padding = &mut paddings[idx];
+        while padding.ends.len() < levels {
+            let unit = options.indent.unit(style, padding.ends.len() + 1);
+            match (options.guides, unit.chars().next()) {
+                (Guides::Rail(rail), Some(first)) => {
+                    padding.text.push(rail);
+                    padding.text.push_str(&unit[first.len_utf8() ..]);
+                },
+                _ => padding.text.push_str(&unit),
+            }
+            padding.ends.push(padding.text.len());
+        }
+        f(&padding.text[.. padding.ends[levels - 1]])
+    }
+
+    /// The path of the value at `address` if it was written before at
+    /// another path. Otherwise the current path is recorded for it.
+    /// Writing a value at the same path again, e.g. for `Layout::Fit`,
+    /// doesn't count as writing it at another path.
+    pub(crate) fn shared_path(&self, address: usize) -> Option<String> {
+        let path = self.path_string();
+        let mut shared = self.shared.borrow_mut();
+        match shared.get(&address) {
+            Some(first) if *first != path => Some(first.clone()),
+            Some(_) => None,
+            None => {
+                shared.insert(address, path);
+                None
+            },
+        }
+    }
+
+    /// Render the path e.g. `.items[3].name`. The root path is empty.
+    pub fn path_string(&self) -> String {
+        self.path.iter().map(|segment| segment.to_string()).collect()
+    }
+
+    /// The width that `Stringify2::stringify_field` pads field names to
+    /// with `Options::align`, which is 0 unless the current value set it.
+    pub fn name_width(&self) -> usize {
+        self.name_width
+    }
+
+    /// Run `f` with the field names of the current value padded to `width`
+    /// columns by `Stringify2::stringify_field` with `Options::align`, e.g.
+    /// to the width of the longest one.
+    pub fn with_name_width<T, F>(&mut self, width: usize, f: F) -> T
+    where F: FnOnce(&mut Self) -> T {
+        let name_width = std::mem::replace(&mut self.name_width, width);
+        let result = f(self);
+        self.name_width = name_width;
+        result
+    }
+
+    /// Run `f` one level deeper than `self`, for the component of the
+    /// current value that is identified by `segment`.
+    pub fn nested<T, F>(&mut self, segment: PathSegment, f: F) -> T
+    where F: FnOnce(&mut Self) -> T {
+        self.depth += 1;
+        self.path.push(segment);
+        let result = self.with_name_width(0, f);
+        self.path.pop();
+        self.depth -= 1;
+        result
+    }
+
+    /// Run `f` at the same depth as `self`, for the component of the current
+    /// value that is identified by `segment` and written inline, e.g. the
+    /// payload of `Some(..)`, whose lines are indented like those of the
+    /// current value.
+    pub fn inline<T, F>(&mut self, segment: PathSegment, f: F) -> T
+    where F: FnOnce(&mut Self) -> T {
+        self.path.push(segment);
+        let result = self.with_name_width(0, f);
+        self.path.pop();
+        result
+    }
+
+    /// Indent the lines written with the returned guard one level deeper
+    /// than those of `self`, like those of a component of the current value
+    /// but at the same path, until the guard is dropped:
+    /// ```text
+    /// let mut ctx = ctx.indented();
+    /// self.indent(buf, styles, &ctx, &line)?;
+    /// ```
+    pub fn indented(&mut self) -> IndentGuard<'_> {
+        self.depth += 1;
+        IndentGuard { ctx: self }
+    }
+}
+
+/// A guard that keeps a `StringifyContext` one level deeper until it is
+/// dropped (see `StringifyContext::indented`).
+#[derive(Debug)]
+pub struct IndentGuard<'c> {
+    ctx: &'c mut StringifyContext,
+}
+
+impl Deref for IndentGuard<'_> {
+    type Target = StringifyContext;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl DerefMut for IndentGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx
+    }
+}
+
+impl Drop for IndentGuard<'_> {
+    fn drop(&mut self) {
+        self.ctx.depth -= 1;
+    }
+}
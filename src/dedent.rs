@@ -0,0 +1,128 @@
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::newline::Newline;
+use crate::node::Node;
+use crate::options::Layout;
+use crate::stringify::Stringify2;
+use crate::styles::{StyleKey, Styles};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// Strip the indentation that the lines of `text` have in common, e.g.
+/// that of a multi-line string literal in the source code. A first line
+/// that is empty and a last line that is blank are dropped, so that the
+/// text can start and end on lines of their own, and other blank lines
+/// are emptied:
+/// ```text
+/// let query = dedent("
+///     SELECT *
+///     FROM users
+/// ");
+/// assert_eq!(query, "SELECT *\nFROM users");
+/// ```
+pub fn dedent(text: &str) -> Cow<'_, str> {
+    let lines = lines(text);
+    let common = common_indent(&lines);
+    if lines.len() == 1 && common == 0 && lines[0].len() == text.len() {
+        return Cow::Borrowed(text);
+    }
+    let lines: Vec<&str> = lines.iter().map(|line| strip(line, common)).collect();
+    Cow::Owned(lines.join("\n"))
+}
+
+/// The lines of `text` without a first line that is empty and a last
+/// line that is blank.
+fn lines(text: &str) -> Vec<&str> {
+    let text = text.strip_prefix('\n').or_else(|| text.strip_prefix("\r\n")).unwrap_or(text);
+    let mut lines: Vec<&str> = text.lines().collect();
+    if lines.len() > 1 && lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// The number of leading whitespace bytes that the non-blank `lines`
+/// have in common.
+fn common_indent(lines: &[&str]) -> usize {
+    let indents = lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[.. line.len() - line.trim_start().len()]);
+    let mut common: Option<&str> = None;
+    for indent in indents {
+        common = Some(match common {
+            None => indent,
+            Some(common) => {
+                let len = common.bytes().zip(indent.bytes()).take_while(|(a, b)| a == b).count();
+                &common[.. len]
+            },
+        });
+    }
+    common.map_or(0, str::len)
+}
+
+/// Strip the `common` indentation from `line`, or empty it if it's blank.
+fn strip(line: &str, common: usize) -> &str {
+    match line.trim().is_empty() {
+        true => "",
+        false => &line[common ..],
+    }
+}
+
+/// A wrapper that writes multi-line text such as a template or an SQL
+/// query like `dedent` does, with each line after the first indented with
+/// the `"end"` style at the depth of the text, so that it nests within the
+/// surrounding output regardless of the `Quoting` and `Multiline` policies.
+/// A field `sql: Dedented(QUERY)` of a struct is written like:
+/// ```text
+/// Query {
+///     name=by id,
+///     sql=SELECT *
+///       FROM users
+///      WHERE id = 1,
+/// }
+/// ```
+/// With `Layout::Compact`, or if the context is flat, the lines are
+/// written on a single line, separated by a space.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dedented<'a>(pub &'a str);
+
+impl Stringify2 for Dedented<'_> {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let options = styles.options();
+        let lines = lines(self.0);
+        let common = common_indent(&lines);
+        if options.layout == Layout::Compact || ctx.is_flat() {
+            let lines: Vec<&str> = lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty()).collect();
+            buf.write_all(lines.join(" ").as_bytes())?;
+            return Ok(());
+        }
+        let line_style = styles.get_or_default(StyleKey::End).with_newline(Newline::Add);
+        for (idx, line) in lines.iter().enumerate() {
+            let line = strip(line, common);
+            match (idx, line.is_empty()) {
+                (0, _) => {},
+                // Blank lines are written without trailing indentation.
+                (_, true) => buf.write_all(options.line_ending.as_str().as_bytes())?,
+                (_, false) => self.indent(buf, styles, ctx, &line_style)?,
+            }
+            buf.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(Node::String(dedent(self.0).into_owned()))
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+
+    fn is_unset(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+}
@@ -0,0 +1,366 @@
+use crate::context::{PathSegment, StringifyContext};
+use crate::error::StringifyResult;
+use crate::node::Node;
+use crate::options::{Options, Quoting};
+use crate::stringify::Stringify2;
+use crate::styles::{Style, StyleKey, Styles};
+use crate::theme::{Color, Role, Theme};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::io::Write;
+
+/// Describe the differences between `a` and `b`, e.g. for the failure
+/// message of a test. Both values are described as `Node`s, which are
+/// walked in lockstep: struct fields are matched by name, map entries by
+/// key, set elements by value and other elements by index. Only the paths to the members that differ
+/// are written, between the opening and closing lines of their containers
+/// as context, with each run of equal members written as the `elision`
+/// marker of the `Options`. The value of
+/// `a` at a path is written on a line starting with `-`, and that of `b`
+/// on a line starting with `+`, each on a single line with `styles`.
+/// If the values are equal, the result is empty.
+pub fn stringify_diff<A, B>(a: &A, b: &B, styles: &Styles) -> StringifyResult<String>
+where A: Stringify2 + ?Sized,
+      B: Stringify2 + ?Sized {
+    let (a, b) = (Node::of(a, styles)?, Node::of(b, styles)?);
+    if a == b {
+        return Ok(String::new());
+    }
+    let mut diff = Diff { styles, lines: vec![] };
+    diff.member(0, "", Some(&a), Some(&b), "")?;
+    Ok(diff.lines.join("\n"))
+}
+
+/// Stringify `current` relative to `baseline`, e.g. to log what changed
+/// when a config is reloaded. Both values are described as `Node`s, which
+/// are matched up like `stringify_diff` does. Only the members of `current`
+/// that differ from those of `baseline` are written, and each run of
+/// unchanged members is written as the `elision` marker of the `Options`:
+/// ```text
+/// Config {
+///     …,
+///     port=8081,
+///     …,
+/// }
+/// ```
+/// Other values that differ, e.g. tuples, are written in full, and members
+/// that only `baseline` has are left out. If the values are equal, the
+/// result is the marker alone.
+pub fn stringify_delta<C, B>(current: &C, baseline: &B, styles: &Styles) -> StringifyResult<String>
+where C: Stringify2 + ?Sized,
+      B: Stringify2 + ?Sized {
+    let (current, baseline) = (Node::of(current, styles)?, Node::of(baseline, styles)?);
+    Delta { current: &current, baseline: Some(&baseline) }.stringify_new(styles)
+}
+
+/// Assert that two values have the same stringification, like `assert_eq!`
+/// does for `Debug` values. On failure, the panic message is the diff of
+/// the values (see `stringify_diff`) rather than both of them in full, with
+/// strings quoted, and with removed and added lines in red and green if
+/// colors are appropriate (see `Theme::auto`). A custom message can be
+/// added like for `assert_eq!`:
+/// ```text
+/// assert_stringify_eq!(config, expected, "after loading {}", path);
+/// ```
+#[macro_export]
+macro_rules! assert_stringify_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::__assert_stringify_eq(&$left, &$right, None)
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::__assert_stringify_eq(&$left, &$right, Some(format_args!($($arg)+)))
+    };
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn __assert_stringify_eq<A, B>(left: &A, right: &B, message: Option<fmt::Arguments>)
+where A: Stringify2 + ?Sized,
+      B: Stringify2 + ?Sized {
+    let styles = Styles::default().with_options(Options::default().with_quoting(Quoting::Quoted));
+    let diff = match stringify_diff(left, right, &styles) {
+        Ok(diff) if diff.is_empty() => return,
+        Ok(diff) => diff,
+        Err(_) => panic!("assertion `left == right` failed: the values can't be stringified"),
+    };
+    let colored = Theme::dark().auto() != Theme::plain();
+    let lines: Vec<String> = diff.lines().map(|line| match line.chars().next() {
+        Some('-') if colored => format!("\x1b[{}m{}\x1b[0m", Color::Red.ansi_code(), line),
+        Some('+') if colored => format!("\x1b[{}m{}\x1b[0m", Color::Green.ansi_code(), line),
+        _ => line.to_string(),
+    }).collect();
+    match message {
+        Some(message) => panic!("assertion `left == right` failed: {}\ndiff (- left, + right):\n{}",
+                                message, lines.join("\n")),
+        None => panic!("assertion `left == right` failed\ndiff (- left, + right):\n{}",
+                       lines.join("\n")),
+    }
+}
+
+/// The lines of a diff, which are prefixed with a marker and indented by
+/// 4 spaces per level.
+struct Diff<'s> {
+    styles: &'s Styles,
+    lines: Vec<String>,
+}
+
+/// A member of one or both containers being diffed: its field name or
+/// key, followed by the separator before its value, and its values.
+type Member<'n> = (String, Option<&'n Node>, Option<&'n Node>);
+
+impl Diff<'_> {
+    /// Add the lines of the member that is written as `name` followed by
+    /// its value and `separator`, which has the value `a` on the left and
+    /// `b` on the right. Containers of the same kind and label on both
+    /// sides are diffed member by member.
+    fn member(&mut self,
+              level: usize,
+              name: &str,
+              a: Option<&Node>,
+              b: Option<&Node>,
+              separator: &str) -> StringifyResult<()> {
+        if let (Some(a), Some(b)) = (a, b) {
+            if let Some(members) = self.members(a, b)? {
+                let (open, close) = delimiters(self.styles.options(), a);
+                self.push(' ', level, &format!("{}{}", name, open));
+                let mut equal = false;
+                for (name, a, b) in &members {
+                    if a == b {
+                        if !equal {
+                            self.push(' ', level + 1, self.styles.options().elision.text());
+                        }
+                        equal = true;
+                        continue;
+                    }
+                    equal = false;
+                    let separator = self.styles.options().delimiters.separator.clone();
+                    self.member(level + 1, name, *a, *b, &separator)?;
+                }
+                self.push(' ', level, &format!("{}{}", close, separator));
+                return Ok(());
+            }
+        }
+        if let Some(a) = a {
+            let value = self.single_line(a)?;
+            self.push('-', level, &format!("{}{}{}", name, value, separator));
+        }
+        if let Some(b) = b {
+            let value = self.single_line(b)?;
+            self.push('+', level, &format!("{}{}{}", name, value, separator));
+        }
+        Ok(())
+    }
+
+    /// The members of `a` and `b` matched up with each other, if they are
+    /// containers of the same kind with the same label.
+    fn members<'n>(&self, a: &'n Node, b: &'n Node) -> StringifyResult<Option<Vec<Member<'n>>>> {
+        let delimiters = &self.styles.options().delimiters;
+        let members = match (a, b) {
+            (Node::Seq { label: la, items: ia }, Node::Seq { label: lb, items: ib })
+                | (Node::Tuple { label: la, items: ia }, Node::Tuple { label: lb, items: ib })
+                if la == lb => {
+                (0 .. ia.len().max(ib.len()))
+                    .map(|idx| (String::new(), ia.get(idx), ib.get(idx)))
+                    .collect()
+            },
+            (Node::Set { label: la, items: ia }, Node::Set { label: lb, items: ib }) if la == lb => {
+                let (in_a, in_b): (HashSet<&Node>, HashSet<&Node>) = (ia.iter().collect(), ib.iter().collect());
+                ia.iter()
+                    .map(|item| (String::new(), Some(item), in_b.get(item).copied()))
+                    .chain(ib.iter().filter(|item| !in_a.contains(item)).map(|item| (String::new(), None, Some(item))))
+                    .collect()
+            },
+            (Node::Map { label: la, entries: ea }, Node::Map { label: lb, entries: eb })
+                if la == lb => {
+                let (in_a, in_b) = (index(ea), index(eb));
+                let mut members = vec![];
+                for (key, value) in ea {
+                    let name = format!("{}{}", self.single_line(key)?, delimiters.key_value);
+                    members.push((name, Some(value), in_b.get(key).copied()));
+                }
+                for (key, value) in eb.iter().filter(|(key, _)| !in_a.contains_key(key)) {
+                    let name = format!("{}{}", self.single_line(key)?, delimiters.key_value);
+                    members.push((name, None, Some(value)));
+                }
+                members
+            },
+            (Node::Struct { label: la, fields: fa }, Node::Struct { label: lb, fields: fb })
+                if la == lb => {
+                let (in_a, in_b) = (index(fa), index(fb));
+                let mut members = vec![];
+                for (field, value) in fa {
+                    let name = format!("{}{}", field, delimiters.field_value);
+                    members.push((name, Some(value), in_b.get(field).copied()));
+                }
+                for (field, value) in fb.iter().filter(|(field, _)| !in_a.contains_key(field)) {
+                    members.push((format!("{}{}", field, delimiters.field_value), None, Some(value)));
+                }
+                members
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(members))
+    }
+
+    /// `node` stringified on a single line.
+    fn single_line(&self, node: &Node) -> StringifyResult<String> {
+        let mut buf = vec![];
+        StringifyContext::new().flat(|ctx| node.stringify(&mut buf, self.styles, ctx))?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    fn push(&mut self, marker: char, level: usize, text: &str) {
+        self.lines.push(format!("{} {}{}", marker, "    ".repeat(level), text));
+    }
+}
+
+/// The values of the fields or entries of a container by their names or
+/// keys, where the first of several with the same name or key is kept.
+fn index<K, V>(members: &[(K, V)]) -> HashMap<&K, &V>
+where K: Eq + Hash {
+    let mut index = HashMap::with_capacity(members.len());
+    for (key, value) in members {
+        index.entry(key).or_insert(value);
+    }
+    index
+}
+
+/// The opening and closing delimiters of the container `node`.
+fn delimiters(options: &Options, node: &Node) -> (String, Cow<'static, str>) {
+    let delimiters = &options.delimiters;
+    match node {
+        Node::Seq { label, .. } => (options.labels.open(label, &delimiters.seq.0), delimiters.seq.1.clone()),
+        Node::Set { label, .. } => (options.labels.open(label, &delimiters.set.0), delimiters.set.1.clone()),
+        Node::Tuple { label, .. } if label.is_empty() => {
+            (delimiters.tuple.0.to_string(), delimiters.tuple.1.clone())
+        },
+        Node::Tuple { label, .. } => (format!("{}(", label), Cow::Borrowed(")")),
+        Node::Map { label, .. } => (options.labels.open(label, &delimiters.map.0), delimiters.map.1.clone()),
+        Node::Struct { label, .. } => (format!("{} {{", label), Cow::Borrowed("}")),
+        _ => (String::new(), Cow::Borrowed("")),
+    }
+}
+
+/// A `Node` of the current value, written relative to the matching `Node`
+/// of the baseline value, if there is one (see `stringify_delta`).
+struct Delta<'n> {
+    current: &'n Node,
+    baseline: Option<&'n Node>,
+}
+
+/// A member of a container in a delta, or a run of unchanged members.
+enum Change<'n> {
+    Field(&'n str, Delta<'n>),
+    Item(usize, Delta<'n>),
+    Entry(&'n Node, Delta<'n>),
+    Unchanged,
+}
+
+impl<'n> Delta<'n> {
+    fn is_unchanged(&self) -> bool {
+        self.baseline == Some(self.current)
+    }
+
+    /// The members of `current` matched up with those of `baseline`, if
+    /// they are structs, sequences, sets or maps with the same label, where
+    /// each run of unchanged members is a single `Change::Unchanged`.
+    fn changes(&self) -> Option<Vec<Change<'n>>> {
+        let changes: Vec<Change> = match (self.current, self.baseline?) {
+            (Node::Struct { label: lc, fields: fc }, Node::Struct { label: lb, fields: fb })
+                if lc == lb => {
+                let baseline = index(fb);
+                fc.iter().map(|(field, value)| {
+                    Change::Field(field, Delta { current: value, baseline: baseline.get(field).copied() })
+                }).collect()
+            },
+            (Node::Seq { label: lc, items: ic }, Node::Seq { label: lb, items: ib }) if lc == lb => {
+                ic.iter().enumerate()
+                    .map(|(idx, item)| Change::Item(idx, Delta { current: item, baseline: ib.get(idx) }))
+                    .collect()
+            },
+            (Node::Set { label: lc, items: ic }, Node::Set { label: lb, items: ib }) if lc == lb => {
+                let baseline: HashSet<&Node> = ib.iter().collect();
+                ic.iter().enumerate()
+                    .map(|(idx, item)| Change::Item(idx, Delta { current: item, baseline: baseline.get(item).copied() }))
+                    .collect()
+            },
+            (Node::Map { label: lc, entries: ec }, Node::Map { label: lb, entries: eb }) if lc == lb => {
+                let baseline = index(eb);
+                ec.iter().map(|(key, value)| {
+                    Change::Entry(key, Delta { current: value, baseline: baseline.get(key).copied() })
+                }).collect()
+            },
+            _ => return None,
+        };
+        let mut collapsed = vec![];
+        for change in changes {
+            let unchanged = match &change {
+                Change::Field(_, delta) | Change::Item(_, delta) | Change::Entry(_, delta) => {
+                    delta.is_unchanged()
+                },
+                Change::Unchanged => true,
+            };
+            match unchanged {
+                true if matches!(collapsed.last(), Some(Change::Unchanged)) => {},
+                true => collapsed.push(Change::Unchanged),
+                false => collapsed.push(change),
+            }
+        }
+        Some(collapsed)
+    }
+}
+
+impl Stringify2 for Delta<'_> {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let options = styles.options();
+        if self.is_unchanged() {
+            options.theme.write(buf, Role::Container, options.elision.text())?;
+            return Ok(());
+        }
+        let changes = match self.changes() {
+            Some(changes) => changes,
+            None => return self.current.stringify(buf, styles, ctx),
+        };
+        let end: Style = styles.get_or_default(StyleKey::End);
+        let (open, close) = delimiters(options, self.current);
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+        options.theme.write(buf, Role::Container, &open)?;
+        for (idx, change) in changes.iter().enumerate() {
+            match change {
+                Change::Field(name, delta) => self.stringify_field(buf, styles, ctx, name, delta)?,
+                Change::Item(idx, delta) => ctx.nested(PathSegment::Index(*idx), |ctx| {
+                    self.indent(buf, styles, ctx, &end)?;
+                    delta.stringify_component(buf, styles, ctx)
+                })?,
+                Change::Entry(key, delta) => {
+                    let segment = PathSegment::Key(key.stringify_primitive_new()?);
+                    ctx.nested(segment, |ctx| {
+                        self.indent(buf, styles, ctx, &styles.get_or(StyleKey::Key, end.clone()))?;
+                        key.stringify(buf, styles, ctx)?;
+                        buf.write_all(options.delimiters.key_value.as_bytes())?;
+                        delta.stringify_component(buf, styles, ctx)
+                    })?
+                },
+                Change::Unchanged => {
+                    match self.current {
+                        Node::Struct { .. } => {
+                            self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Name))?
+                        },
+                        _ => self.indent(buf, styles, &ctx.indented(), &end)?,
+                    }
+                    options.theme.write(buf, Role::Container, options.elision.text())?;
+                },
+            }
+            self.write_separator(buf, styles, ctx, idx + 1 == changes.len())?;
+        }
+        self.indent(buf, styles, ctx, &end)?;
+        options.theme.write(buf, Role::Container, &close)?;
+        Ok(())
+    }
+}
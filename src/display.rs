@@ -0,0 +1,84 @@
+use crate::options::Layout;
+use crate::stringifier::Stringifier;
+use crate::stringify::Stringify2;
+use crate::styles::Styles;
+use std::fmt;
+
+/// A value together with the `Styles` to stringify it with, which
+/// implements `Display` (see `Stringify2::display_with`). The value is
+/// only stringified when it is formatted, so e.g. the arguments of log
+/// statements that are filtered out cost nothing:
+/// ```text
+/// info!("loaded {}", config.display_with(&styles));
+/// ```
+pub struct Stringified<'a, T: ?Sized> {
+    value: &'a T,
+    styles: &'a Styles,
+}
+
+impl<'a, T> Stringified<'a, T>
+where T: Stringify2 + ?Sized {
+    pub fn new(value: &'a T, styles: &'a Styles) -> Self {
+        Self { value, styles }
+    }
+}
+
+impl<T> fmt::Display for Stringified<'_, T>
+where T: Stringify2 + ?Sized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.stringify_fmt(f, self.styles).map_err(|_| fmt::Error)
+    }
+}
+
+/// Implement `Display` for each of the given types by their `Stringify2`
+/// impl, with the `Styles` of the default `Stringifier`. Values are written
+/// on a single line, or indented over multiple lines with the alternate
+/// flag, i.e. `{:#}`:
+/// ```text
+/// impl_display_via_stringify!(Config, Server);
+/// println!("{:#}", config);
+/// ```
+#[macro_export]
+macro_rules! impl_display_via_stringify {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl ::std::fmt::Display for $ty {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    $crate::__fmt_via_stringify(self, f)
+                }
+            }
+        )+
+    };
+}
+
+/// Implement `Debug` for each of the given types like
+/// `impl_display_via_stringify!` implements `Display`, so that e.g.
+/// `{:?}` writes a single line and `{:#?}` an indented stringification.
+#[macro_export]
+macro_rules! impl_debug_via_stringify {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl ::std::fmt::Debug for $ty {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    $crate::__fmt_via_stringify(self, f)
+                }
+            }
+        )+
+    };
+}
+
+/// Write `value` to `f` with the `Styles` of the default `Stringifier`,
+/// with `Layout::Compact` unless the alternate flag of `f` is set.
+#[doc(hidden)]
+pub fn __fmt_via_stringify<T>(value: &T, f: &mut fmt::Formatter) -> fmt::Result
+where T: Stringify2 + ?Sized {
+    let stringifier = Stringifier::default();
+    let styles = match f.alternate() {
+        true => stringifier.styles().clone(),
+        false => {
+            let options = stringifier.options().clone().with_layout(Layout::Compact);
+            stringifier.styles().clone().with_options(options)
+        },
+    };
+    value.stringify_fmt(f, &styles).map_err(|_| fmt::Error)
+}
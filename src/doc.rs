@@ -0,0 +1,201 @@
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::layout::ColumnWriter;
+use crate::newline::Newline;
+use crate::options::{Layout, Options};
+use crate::stringify::Stringify2;
+use crate::styles::{StyleKey, Styles};
+use crate::theme::Role;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::io::Write;
+use std::rc::Rc;
+
+/// A document in the style of Wadler's "prettier printer": a description
+/// of the structure of some output, which is laid out against a target
+/// width when it is stringified. `Stringify2` impls can build a `Doc` and
+/// stringify that, rather than deciding on line breaks and indentation
+/// themselves:
+/// ```text
+/// let items = self.iter().map(|item| Doc::text(item.to_string()));
+/// let doc = Doc::group(
+///     Doc::text("[")
+///         .append(Doc::nest(1, Doc::softline().append(Doc::join(items, Doc::text(",").append(Doc::line())))))
+///         .append(Doc::softline())
+///         .append(Doc::text("]"))
+/// );
+/// doc.stringify(buf, styles, ctx)
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Doc(Node);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node {
+    Nil,
+    Text(Cow<'static, str>, Option<Role>),
+    Line(&'static str),
+    Concat(Vec<Doc>),
+    Nest(usize, Box<Doc>),
+    Group(Box<Doc>),
+}
+
+impl Doc {
+    /// The empty document.
+    pub fn nil() -> Self {
+        Doc(Node::Nil)
+    }
+
+    /// A piece of text, which should not contain line breaks.
+    pub fn text<T>(text: T) -> Self
+    where T: Into<Cow<'static, str>> {
+        Doc(Node::Text(text.into(), None))
+    }
+
+    /// A piece of text that is colored by the `Theme` for `role`.
+    pub fn styled<T>(text: T, role: Role) -> Self
+    where T: Into<Cow<'static, str>> {
+        Doc(Node::Text(text.into(), Some(role)))
+    }
+
+    /// A line break, or a space if the enclosing group is laid out flat.
+    pub fn line() -> Self {
+        Doc(Node::Line(" "))
+    }
+
+    /// A line break, or nothing if the enclosing group is laid out flat.
+    pub fn softline() -> Self {
+        Doc(Node::Line(""))
+    }
+
+    /// The concatenation of `docs`.
+    pub fn concat<I>(docs: I) -> Self
+    where I: IntoIterator<Item = Doc> {
+        Doc(Node::Concat(docs.into_iter().collect()))
+    }
+
+    /// The concatenation of `docs` with `separator` between each of them.
+    pub fn join<I>(docs: I, separator: Doc) -> Self
+    where I: IntoIterator<Item = Doc> {
+        let mut joined = vec![];
+        for (idx, doc) in docs.into_iter().enumerate() {
+            if idx > 0 {
+                joined.push(separator.clone());
+            }
+            joined.push(doc);
+        }
+        Doc(Node::Concat(joined))
+    }
+
+    /// `doc` with the lines that its line breaks start indented
+    /// `levels` deeper.
+    pub fn nest(levels: usize, doc: Doc) -> Self {
+        Doc(Node::Nest(levels, Box::new(doc)))
+    }
+
+    /// `doc` laid out flat, i.e. with all of its line breaks replaced,
+    /// if it fits on the current line, and with line breaks otherwise.
+    pub fn group(doc: Doc) -> Self {
+        Doc(Node::Group(Box::new(doc)))
+    }
+
+    /// The concatenation of `self` and `doc`.
+    pub fn append(self, doc: Doc) -> Self {
+        match self.0 {
+            Node::Concat(mut docs) => {
+                docs.push(doc);
+                Doc(Node::Concat(docs))
+            },
+            node => Doc(Node::Concat(vec![Doc(node), doc])),
+        }
+    }
+
+    /// Lay out `self` against `width` columns.
+    pub fn pretty(&self, width: usize) -> StringifyResult<String> {
+        let options = Options::default().with_layout(Layout::Fit { width });
+        self.stringify_new(&Styles::default().with_options(options))
+    }
+}
+
+/// A `Doc` is laid out against the `width` of `Layout::Fit` in the `Options`,
+/// starting at the column that the output has reached. With `Layout::Compact`,
+/// or if the context is flat, everything is laid out flat, and with
+/// `Layout::Expanded` every group is broken. Like any value with
+/// `Layout::Fit`, a `Doc` that fits as a whole is written flat, so that it
+/// behaves as if it were a group itself. Line breaks are written like
+/// those of the `"end"` style, nested as deep as `ctx.depth()` plus the
+/// levels of the enclosing `Doc::nest`s.
+impl Stringify2 for Doc {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let options = styles.options();
+        let (width, flat) = match options.layout {
+            _ if ctx.is_flat() => (usize::MAX, true),
+            Layout::Compact => (usize::MAX, true),
+            Layout::Fit { width } => (width, false),
+            Layout::Expanded => (0, false),
+        };
+        let column = Rc::new(Cell::new(ctx.column()));
+        let mut buf = ColumnWriter::new(buf, column.clone());
+        let mut line = styles.get_or_default(StyleKey::End);
+        line.newline = Newline::Add;
+        let mut stack = vec![(0, flat, self)];
+        while let Some((nest, flat, doc)) = stack.pop() {
+            match &doc.0 {
+                Node::Nil => {},
+                Node::Text(text, None) => buf.write_all(text.as_bytes())?,
+                Node::Text(text, Some(role)) => options.theme.write(&mut buf, *role, text)?,
+                Node::Line(text) if flat => buf.write_all(text.as_bytes())?,
+                Node::Line(_) => {
+                    line.indent_level = nest;
+                    self.indent(&mut buf, styles, ctx, &line)?;
+                },
+                Node::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (nest, flat, doc))),
+                Node::Nest(levels, doc) => stack.push((nest + levels, flat, doc)),
+                Node::Group(doc) => {
+                    let remaining = width.saturating_sub(column.get());
+                    let flat = flat || fits(remaining, doc, &stack);
+                    stack.push((nest, flat, doc));
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return `true` if `doc` laid out flat, followed by the `rest` of the
+/// document up to its next line break, fits within `remaining` columns.
+fn fits(remaining: usize, doc: &Doc, rest: &[(usize, bool, &Doc)]) -> bool {
+    let mut remaining = remaining;
+    let mut stack = vec![(true, doc)];
+    let mut rest = rest.iter().rev();
+    loop {
+        let (flat, doc) = match stack.pop() {
+            Some(entry) => entry,
+            None => match rest.next() {
+                Some(&(_, flat, doc)) => (flat, doc),
+                None => return true,
+            },
+        };
+        let text: &str = match &doc.0 {
+            Node::Nil => "",
+            Node::Text(text, _) => text,
+            Node::Line(text) if flat => text,
+            Node::Line(_) => return true,
+            Node::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (flat, doc)));
+                ""
+            },
+            Node::Nest(_, doc) | Node::Group(doc) => {
+                stack.push((flat, doc));
+                ""
+            },
+        };
+        match remaining.checked_sub(text.chars().count()) {
+            Some(left) => remaining = left,
+            None => return false,
+        }
+    }
+}
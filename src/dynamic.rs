@@ -0,0 +1,91 @@
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::node::Node;
+use crate::stringify::Stringify2;
+use crate::styles::Styles;
+use std::io::Write;
+
+/// An object safe counterpart of `Stringify2`, which makes it possible
+/// to stringify trait objects e.g. a `Vec<Box<dyn StringifyDyn>>` of
+/// values of different types. Every `Stringify2` type implements it.
+pub trait StringifyDyn {
+    fn stringify_dyn(&self,
+                     buf: &mut dyn Write,
+                     styles: &Styles,
+                     ctx: &mut StringifyContext) -> StringifyResult<()>;
+
+    fn to_node_dyn(&self, styles: &Styles) -> StringifyResult<Node>;
+
+    fn is_unset_dyn(&self) -> bool;
+}
+
+impl<T> StringifyDyn for T
+where T: Stringify2 {
+    fn stringify_dyn(&self,
+                     mut buf: &mut dyn Write,
+                     styles: &Styles,
+                     ctx: &mut StringifyContext) -> StringifyResult<()> {
+        self.stringify(&mut buf, styles, ctx)
+    }
+
+    fn to_node_dyn(&self, styles: &Styles) -> StringifyResult<Node> {
+        Node::of(self, styles)
+    }
+
+    fn is_unset_dyn(&self) -> bool {
+        self.is_unset()
+    }
+}
+
+/// The signature of the functions given to `#[stringify(with = path)]`.
+pub(crate) type WithFn<T> = fn(&T, &mut dyn Write, &Styles, &mut StringifyContext)
+                                 -> StringifyResult<()>;
+
+/// A field value that is stringified by the function given to
+/// `#[stringify(with = path)]` rather than by its own `Stringify2` impl.
+#[doc(hidden)]
+pub struct __With<'v, T: ?Sized> {
+    pub value: &'v T,
+    pub with: WithFn<T>,
+}
+
+impl<T> Stringify2 for __With<'_, T>
+where T: ?Sized {
+    fn stringify<W>(&self,
+                    mut buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        (self.with)(self.value, &mut buf, styles, ctx)
+    }
+}
+
+macro_rules! impl_stringify2_for_trait_objects {
+    ($($object:ty),*) => {
+        $(
+            impl Stringify2 for $object {
+                fn stringify<W>(&self,
+                                buf: &mut W,
+                                styles: &Styles,
+                                ctx: &mut StringifyContext) -> StringifyResult<()>
+                where W: Write {
+                    self.stringify_dyn(buf, styles, ctx)
+                }
+
+                fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+                    self.to_node_dyn(styles)
+                }
+
+                fn is_unset(&self) -> bool {
+                    self.is_unset_dyn()
+                }
+            }
+        )*
+    };
+}
+
+impl_stringify2_for_trait_objects!(
+    dyn StringifyDyn + '_,
+    dyn StringifyDyn + Send + '_,
+    dyn StringifyDyn + Send + Sync + '_
+);
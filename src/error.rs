@@ -1,15 +1,128 @@
-use ioe::IoError;
+use crate::context::StringifyContext;
+use std::error::Error;
+use std::fmt;
 use std::io;
+use std::sync::Arc;
 
 pub type StringifyResult<T> = Result<T, StringifyError>;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum StringifyError {
     IoError(IoError),
     StyleNotFound { name: &'static str },
+    InvalidStylesheet { message: String },
+    InvalidSelector { selector: String },
+    /// A value was nested deeper than the `depth_limit` of the `Options`.
+    DepthLimitExceeded { limit: usize, path: String },
+    /// The stringification of a value is not valid UTF-8, e.g. because a
+    /// `Stringify2` impl wrote raw bytes to a `String`.
+    InvalidUtf8 { path: String },
+}
+
+impl StringifyError {
+    /// The path of the value that caused `self`, if it is known, e.g.
+    /// `.items[3].name`. The path of the root value is empty.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            StringifyError::DepthLimitExceeded { path, .. } => Some(path),
+            StringifyError::InvalidUtf8 { path } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Attach the path of the value in `ctx` to `self`, unless the path
+    /// of a value nested deeper was attached already.
+    pub(crate) fn at(self, ctx: &StringifyContext) -> Self {
+        match self {
+            StringifyError::InvalidUtf8 { path } if path.is_empty() =>
+                StringifyError::InvalidUtf8 { path: ctx.path_string() },
+            err => err,
+        }
+    }
 }
 
 impl From<io::Error> for StringifyError {
     fn from(err: io::Error) -> StringifyError {
-        StringifyError::IoError(IoError::from(err))
+        if err.get_ref().is_some_and(|inner| inner.is::<NotUtf8>()) {
+            return StringifyError::InvalidUtf8 { path: String::new() };
+        }
+        StringifyError::IoError(IoError(Arc::new(err)))
+    }
+}
+
+/// The `io::Error` of a `StringifyError::IoError`. It is shared, so that
+/// the `StringifyError` can be cloned, and errors are equal if their kinds
+/// and messages are.
+#[derive(Clone, Debug)]
+pub struct IoError(Arc<io::Error>);
+
+impl IoError {
+    pub fn kind(&self) -> io::ErrorKind {
+        self.0.kind()
+    }
+
+    pub fn get_ref(&self) -> &io::Error {
+        &self.0
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl Eq for IoError {}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The error inside the `io::Error`s of writes of invalid UTF-8, which
+/// becomes a `StringifyError::InvalidUtf8`.
+#[derive(Debug)]
+pub(crate) struct NotUtf8;
+
+impl fmt::Display for NotUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "stringification is not valid UTF-8")
+    }
+}
+
+impl Error for NotUtf8 {}
+
+/// The `path` of a value as written in error messages.
+fn describe_path(path: &str) -> &str {
+    match path {
+        "" => "the root value",
+        path => path,
+    }
+}
+
+impl fmt::Display for StringifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StringifyError::IoError(err) => write!(f, "writing the stringification failed: {}", err),
+            StringifyError::StyleNotFound { name } => write!(f, "style not found: {}", name),
+            StringifyError::InvalidStylesheet { message } =>
+                write!(f, "invalid stylesheet: {}", message),
+            StringifyError::InvalidSelector { selector } =>
+                write!(f, "invalid selector: {}", selector),
+            StringifyError::DepthLimitExceeded { limit, path } =>
+                write!(f, "depth limit of {} exceeded at {}", limit, describe_path(path)),
+            StringifyError::InvalidUtf8 { path } =>
+                write!(f, "invalid UTF-8 in the stringification of {}", describe_path(path)),
+        }
+    }
+}
+
+impl Error for StringifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StringifyError::IoError(err) => Some(err.get_ref()),
+            _ => None,
+        }
     }
 }
@@ -0,0 +1,187 @@
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::newline::Newline;
+use crate::node::Node;
+use crate::options::Layout;
+use crate::stringify::Stringify2;
+use crate::styles::{Style, StyleKey, Styles};
+use crate::theme::Role;
+use std::borrow::Cow;
+use std::error::Error;
+use std::io::Write;
+
+/// Write `error` followed by its chain of `source()`s, with each cause on
+/// a line of its own after `caused by: `, indented one level deeper than
+/// the error that it caused. With `Layout::Compact`, or if the context is
+/// flat, the chain is written on a single line, separated by `: `.
+pub(crate) fn stringify_error_chain<S, W>(this: &S,
+                                          error: &dyn Error,
+                                          buf: &mut W,
+                                          styles: &Styles,
+                                          ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let mut line = styles.get_or_default(StyleKey::End);
+    line.newline = Newline::Add;
+    line.indent_level = 0;
+    write!(buf, "{}", error)?;
+    stringify_causes(this, error.source(), buf, styles, ctx, &line)
+}
+
+/// Write `source`, if any, as the cause of an error that was written at the
+/// depth of `ctx`, followed by its own causes, each one level deeper than
+/// the last. Causes that start a line of their own do so with `line`.
+fn stringify_causes<S, W>(this: &S,
+                          source: Option<&dyn Error>,
+                          buf: &mut W,
+                          styles: &Styles,
+                          ctx: &mut StringifyContext,
+                          line: &Style) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let cause = match source {
+        Some(cause) => cause,
+        None => return Ok(()),
+    };
+    let options = styles.options();
+    let mut ctx = ctx.indented();
+    match options.layout == Layout::Compact || ctx.is_flat() {
+        true => buf.write_all(b": ")?,
+        false => {
+            this.indent(buf, styles, &ctx, line)?;
+            options.theme.write(buf, Role::Container, "caused by: ")?;
+        },
+    }
+    write!(buf, "{}", cause)?;
+    stringify_causes(this, cause.source(), buf, styles, &mut ctx, line)
+}
+
+/// Write a section of an error report, e.g. a backtrace, as its `title`
+/// one level deeper than the error, followed by the lines of `text` one
+/// level deeper still, without their common indentation. With
+/// `Layout::Compact`, or if the context is flat, nothing is written.
+#[cfg(any(feature = "anyhow", feature = "eyre"))]
+pub(crate) fn stringify_report_section<S, W>(this: &S,
+                                             title: &str,
+                                             text: &str,
+                                             buf: &mut W,
+                                             styles: &Styles,
+                                             ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let options = styles.options();
+    if options.layout == Layout::Compact || ctx.is_flat() {
+        return Ok(());
+    }
+    let mut line = styles.get_or_default(StyleKey::End);
+    line.newline = Newline::Add;
+    line.indent_level = 0;
+    let mut ctx = ctx.indented();
+    this.indent(buf, styles, &ctx, &line)?;
+    options.theme.write(buf, Role::Container, &format!("{}:", title))?;
+    let lines: Vec<&str> = text.lines().filter(|text| !text.trim().is_empty()).collect();
+    let common = lines.iter()
+        .map(|text| text.len() - text.trim_start().len())
+        .min().unwrap_or(0);
+    let ctx = ctx.indented();
+    for text in lines {
+        this.indent(buf, styles, &ctx, &line)?;
+        buf.write_all(&text.as_bytes()[common ..])?;
+    }
+    Ok(())
+}
+
+/// Describe `error` and its chain of `source()`s as a sequence of their
+/// messages, labelled `Error`.
+pub(crate) fn error_chain_node(error: &dyn Error) -> Node {
+    let mut items = vec![Node::String(error.to_string())];
+    let mut source = error.source();
+    while let Some(cause) = source {
+        items.push(Node::String(cause.to_string()));
+        source = cause.source();
+    }
+    Node::Seq { label: Cow::Borrowed("Error"), items }
+}
+
+macro_rules! impl_stringify2_for_errors {
+    ($($object:ty),*) => {
+        $(
+            /// An error is written as its causal chain (see `Error::source`),
+            /// with each cause indented one level deeper than the last.
+            impl Stringify2 for $object {
+                fn stringify<W>(&self,
+                                buf: &mut W,
+                                styles: &Styles,
+                                ctx: &mut StringifyContext) -> StringifyResult<()>
+                where W: Write {
+                    stringify_error_chain(self, self, buf, styles, ctx)
+                }
+
+                fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+                    Ok(error_chain_node(self))
+                }
+            }
+        )*
+    };
+}
+
+impl_stringify2_for_errors!(
+    dyn Error + '_,
+    dyn Error + Send + '_,
+    dyn Error + Send + Sync + '_
+);
+
+/// An `anyhow::Error` is written as its causal chain, followed by its
+/// backtrace if one was captured.
+#[cfg(feature = "anyhow")]
+impl Stringify2 for anyhow::Error {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let error: &(dyn Error + Send + Sync + 'static) = self.as_ref();
+        stringify_error_chain(self, error, buf, styles, ctx)?;
+        let backtrace = self.backtrace();
+        if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+            let text = backtrace.to_string();
+            stringify_report_section(self, "Stack backtrace", &text, buf, styles, ctx)?;
+        }
+        Ok(())
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(error_chain_node(self.as_ref()))
+    }
+}
+
+/// An `eyre::Report` is written as its causal chain, followed by the
+/// sections that its handler adds to the report, e.g. the location where
+/// it was created and its backtrace.
+#[cfg(feature = "eyre")]
+impl Stringify2 for eyre::Report {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let error: &(dyn Error + Send + Sync + 'static) = self.as_ref();
+        stringify_error_chain(self, error, buf, styles, ctx)?;
+        // Handlers only expose their sections through the `Debug` output of
+        // the report, where they follow the message and the causes, each
+        // as a title line followed by its lines, separated by empty lines.
+        let report = format!("{:?}", self);
+        let sections = report.split("\n\n").skip(1)
+            .filter(|section| !section.starts_with("Caused by:"));
+        for section in sections {
+            let (title, text) = section.split_once('\n').unwrap_or((section, ""));
+            let title = title.trim().trim_end_matches(':');
+            stringify_report_section(self, title, text, buf, styles, ctx)?;
+        }
+        Ok(())
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(error_chain_node(self.as_ref()))
+    }
+}
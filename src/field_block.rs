@@ -0,0 +1,101 @@
+use crate::context::{PathSegment, StringifyContext};
+use crate::dynamic::StringifyDyn;
+use crate::error::StringifyResult;
+use crate::layout::display_width;
+use crate::stringify::{__name_width, write_padding, Stringify2};
+use crate::styles::{Style, StyleKey, Styles};
+use crate::theme::Role;
+use std::io::Write;
+
+/// The named fields of a value, which are written with their names padded
+/// to the width of the longest one so that the values line up, regardless
+/// of `Options::align`. A hand-written impl collects its fields and writes
+/// them between its own opening and closing delimiters:
+/// ```text
+/// self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+/// styles.options().theme.write(buf, Role::Container, "Point {")?;
+/// FieldBlock::new()
+///     .field("x", &self.x)
+///     .field("label", &self.label)
+///     .with_separator(" = ")
+///     .stringify(self, buf, styles, ctx)?;
+/// self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::End))?;
+/// styles.options().theme.write(buf, Role::Container, "}")?;
+/// ```
+/// The derive writes the fields of a struct or variant annotated with
+/// `#[stringify(align)]` in the same way.
+#[derive(Default)]
+pub struct FieldBlock<'v> {
+    fields: Vec<(&'v str, &'v dyn StringifyDyn)>,
+    separator: Option<&'v str>,
+}
+
+impl<'v> FieldBlock<'v> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field named `name` after the fields that were added so far.
+    pub fn field(mut self, name: &'v str, value: &'v dyn StringifyDyn) -> Self {
+        self.fields.push((name, value));
+        self
+    }
+
+    /// Add a field like `field` does, but only if `written`, e.g. to
+    /// leave out a field whose value is the default.
+    pub fn field_if(self, written: bool, name: &'v str, value: &'v dyn StringifyDyn) -> Self {
+        match written {
+            true => self.field(name, value),
+            false => self,
+        }
+    }
+
+    /// Write `separator` between each name and value rather than the
+    /// `field_value` delimiter of the `Options`.
+    pub fn with_separator(mut self, separator: &'v str) -> Self {
+        self.separator = Some(separator);
+        self
+    }
+
+    /// The width that the names of the fields are padded to, unless some
+    /// of them are left out by `Options::skip_unset`.
+    pub fn name_width(&self) -> usize {
+        __name_width(self.fields.iter().map(|(name, _)| (*name, true)))
+    }
+
+    /// Whether none of the fields are written with the `Options` of
+    /// `styles`, e.g. to write `Point {}` rather than opening a block.
+    pub fn is_empty(&self, styles: &Styles) -> bool {
+        let skip_unset = styles.options().skip_unset;
+        self.fields.iter().all(|(_, value)| skip_unset && value.is_unset_dyn())
+    }
+
+    /// Write the fields like `Stringify2::stringify_field` does, each
+    /// followed by a separator, where `this` is the value they belong to.
+    /// With `Options::skip_unset`, unset fields are left out.
+    pub fn stringify<S, W>(&self,
+                           this: &S,
+                           buf: &mut W,
+                           styles: &Styles,
+                           ctx: &mut StringifyContext) -> StringifyResult<()>
+    where S: Stringify2 + ?Sized,
+          W: Write {
+        let options = styles.options();
+        let name_style: Style = styles.get_or_default(StyleKey::Name);
+        let separator = self.separator.unwrap_or(&options.delimiters.field_value);
+        let written = |(_, value): &&(&str, &dyn StringifyDyn)| !(options.skip_unset && value.is_unset_dyn());
+        let count = self.fields.iter().filter(written).count();
+        let name_width = __name_width(self.fields.iter().map(|field| (field.0, written(&field))));
+        for (idx, (name, value)) in self.fields.iter().filter(written).enumerate() {
+            this.indent(buf, styles, ctx, &name_style)?;
+            options.theme.write(buf, Role::FieldName, name)?;
+            write_padding(buf, styles, ctx, display_width(name), name_width)?;
+            buf.write_all(separator.as_bytes())?;
+            ctx.nested(PathSegment::Field(name.to_string()), |ctx| {
+                value.stringify_component(buf, styles, ctx)
+            })?;
+            this.write_separator(buf, styles, ctx, idx + 1 == count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::error::NotUtf8;
+use std::fmt;
+use std::io::{self, Write};
+use std::str;
+
+/// An adapter that makes a `fmt::Write` such as a `String` or a
+/// `fmt::Formatter` usable as an `io::Write`.
+/// Since the bytes written to an `io::Write` are not required to be split
+/// on `char` boundaries, an incomplete UTF-8 sequence at the end of a write
+/// is held back until the rest of it is written.
+pub(crate) struct FmtWriter<'f, F> {
+    inner: &'f mut F,
+    pending: Vec<u8>,
+}
+
+impl<'f, F> FmtWriter<'f, F>
+where F: fmt::Write {
+    pub(crate) fn new(inner: &'f mut F) -> Self {
+        Self { inner, pending: vec![] }
+    }
+
+    /// Check that no incomplete UTF-8 sequence is left over.
+    pub(crate) fn finish(self) -> io::Result<()> {
+        match self.pending.is_empty() {
+            true => Ok(()),
+            false => Err(invalid_utf8()),
+        }
+    }
+
+    fn write_str(&mut self, text: &str) -> io::Result<()> {
+        self.inner.write_str(text).map_err(io::Error::other)
+    }
+}
+
+impl<F> Write for FmtWriter<'_, F>
+where F: fmt::Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            if let Ok(text) = str::from_utf8(bytes) {
+                self.write_str(text)?;
+                return Ok(bytes.len());
+            }
+        }
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.extend_from_slice(bytes);
+        let valid = match str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => return Err(invalid_utf8()),
+        };
+        let text = str::from_utf8(&pending[.. valid]).map_err(|_| invalid_utf8())?;
+        self.write_str(text)?;
+        pending.drain(.. valid);
+        self.pending = pending;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, NotUtf8)
+}
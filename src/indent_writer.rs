@@ -0,0 +1,126 @@
+use crate::context::StringifyContext;
+use crate::styles::{Style, StyleKey, Styles};
+use std::io::{self, Write};
+use std::ops::{Deref, DerefMut};
+
+/// A writer that indents every line written through it by its current
+/// level, the same way `Stringify2` impls indent their lines, e.g. to
+/// generate code. The indentation follows the `IndentPolicy` and `Guides`
+/// of the `Options` of its `Styles`, with the unit of the `"end"` style.
+/// `push` goes one level deeper until the guard it returns is dropped:
+/// ```text
+/// let mut out = IndentWriter::new(file);
+/// writeln!(out, "fn main() {{")?;
+/// {
+///     let mut body = out.push();
+///     writeln!(body, "println!(\"Hello\");")?;
+/// }
+/// writeln!(out, "}}")?;
+/// ```
+/// Empty lines are not indented.
+pub struct IndentWriter<W> {
+    inner: W,
+    styles: Styles,
+    style: Style,
+    ctx: StringifyContext,
+    level: usize,
+    at_line_start: bool,
+}
+
+impl<W> IndentWriter<W>
+where W: Write {
+    pub fn new(inner: W) -> Self {
+        Self::with_styles(inner, Styles::default())
+    }
+
+    pub fn with_styles(inner: W, styles: Styles) -> Self {
+        let style = styles.get_or_default(StyleKey::End);
+        Self { inner, styles, style, ctx: StringifyContext::new(), level: 0, at_line_start: true }
+    }
+
+    /// The number of levels that lines are indented by.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Indent the lines that are written through the returned guard one
+    /// level deeper, until it is dropped.
+    pub fn push(&mut self) -> Indented<'_, W> {
+        self.level += 1;
+        Indented { writer: self }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> Write for IndentWriter<W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let Self { inner, styles, style, ctx, level, at_line_start } = self;
+        let mut start = 0;
+        for (idx, &byte) in bytes.iter().enumerate() {
+            if *at_line_start && byte != b'\n' && byte != b'\r' {
+                inner.write_all(&bytes[start .. idx])?;
+                start = idx;
+                ctx.padding(styles.options(), style, *level, |padding| inner.write_all(padding.as_bytes()))?;
+                *at_line_start = false;
+            }
+            if byte == b'\n' {
+                *at_line_start = true;
+            }
+        }
+        inner.write_all(&bytes[start ..])?;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A guard that keeps an `IndentWriter` one level deeper until it is
+/// dropped (see `IndentWriter::push`). It writes through the writer.
+pub struct Indented<'w, W>
+where W: Write {
+    writer: &'w mut IndentWriter<W>,
+}
+
+impl<W> Deref for Indented<'_, W>
+where W: Write {
+    type Target = IndentWriter<W>;
+
+    fn deref(&self) -> &Self::Target {
+        self.writer
+    }
+}
+
+impl<W> DerefMut for Indented<'_, W>
+where W: Write {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.writer
+    }
+}
+
+impl<W> Write for Indented<'_, W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.writer.write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W> Drop for Indented<'_, W>
+where W: Write {
+    fn drop(&mut self) {
+        self.writer.level -= 1;
+    }
+}
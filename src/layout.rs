@@ -0,0 +1,285 @@
+use crate::options::LinePrefix;
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// Tracks the column of a line that the next char written to it ends up
+/// in. ANSI escape sequences, such as the colors of a `Theme`, take up no
+/// columns, and neither do the continuation bytes of multi-byte chars.
+#[derive(Copy, Clone, Debug, Default)]
+struct Columns {
+    column: usize,
+    escape: bool,
+}
+
+impl Columns {
+    /// Advance over `bytes`, and return whether they contain a line break.
+    fn advance(&mut self, bytes: &[u8]) -> bool {
+        let mut line_break = false;
+        for &byte in bytes {
+            match byte {
+                _ if self.escape => self.escape = byte == b'[' || !(b'@' ..= b'~').contains(&byte),
+                0x1b => self.escape = true,
+                b'\n' => {
+                    self.column = 0;
+                    line_break = true;
+                },
+                0x80 ..= 0xbf => {},
+                _ => self.column += 1,
+            }
+        }
+        line_break
+    }
+}
+
+/// A writer that forwards to `inner` while keeping track of the column
+/// that the output has reached, which it shares with a `StringifyContext`.
+pub(crate) struct ColumnWriter<'w, W> {
+    inner: &'w mut W,
+    columns: Columns,
+    column: Rc<Cell<usize>>,
+}
+
+impl<'w, W> ColumnWriter<'w, W>
+where W: Write {
+    pub(crate) fn new(inner: &'w mut W, column: Rc<Cell<usize>>) -> Self {
+        let columns = Columns { column: column.get(), escape: false };
+        Self { inner, columns, column }
+    }
+}
+
+impl<W> Write for ColumnWriter<'_, W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.inner.write_all(bytes)?;
+        self.columns.advance(bytes);
+        self.column.set(self.columns.column);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer that forwards to `inner` without the indentation at the end
+/// of each line, i.e. the whitespace that is written while `indenting` is
+/// set, which it shares with a `StringifyContext`. It is held back until
+/// it is clear whether anything other than a line ending follows it on the
+/// same line. Whitespace that belongs to values is written as is.
+pub(crate) struct TrimWriter<'w, W> {
+    inner: &'w mut W,
+    indenting: Rc<Cell<bool>>,
+    pending: Vec<u8>,
+}
+
+impl<'w, W> TrimWriter<'w, W>
+where W: Write {
+    pub(crate) fn new(inner: &'w mut W, indenting: Rc<Cell<bool>>) -> Self {
+        Self { inner, indenting, pending: vec![] }
+    }
+}
+
+impl<W> Write for TrimWriter<'_, W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if !self.indenting.get() {
+            match bytes.first() {
+                Some(b'\n' | b'\r') => self.pending.clear(),
+                Some(_) => {
+                    self.inner.write_all(&self.pending)?;
+                    self.pending.clear();
+                },
+                None => {},
+            }
+            self.inner.write_all(bytes)?;
+            return Ok(bytes.len());
+        }
+        // The start of the bytes that are written as they are.
+        let mut start = 0;
+        for (idx, &byte) in bytes.iter().enumerate() {
+            match byte {
+                b' ' | b'\t' => {
+                    self.inner.write_all(&bytes[start .. idx])?;
+                    self.pending.push(byte);
+                    start = idx + 1;
+                },
+                _ if !self.pending.is_empty() => {
+                    self.inner.write_all(&self.pending)?;
+                    self.pending.clear();
+                },
+                _ => {},
+            }
+        }
+        self.inner.write_all(&bytes[start ..])?;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer that forwards to `inner` and keeps track of whether anything
+/// other than indentation has been written on the current line, in the
+/// `started` cell that it shares with a `StringifyContext`, so that e.g.
+/// a `Multiline::Margin` string doesn't start with a line break when it
+/// starts on a line of its own.
+pub(crate) struct LineStartWriter<'w, W> {
+    inner: &'w mut W,
+    indenting: Rc<Cell<bool>>,
+    started: Rc<Cell<bool>>,
+}
+
+impl<'w, W> LineStartWriter<'w, W>
+where W: Write {
+    pub(crate) fn new(inner: &'w mut W,
+                      indenting: Rc<Cell<bool>>,
+                      started: Rc<Cell<bool>>) -> Self {
+        Self { inner, indenting, started }
+    }
+}
+
+impl<W> Write for LineStartWriter<'_, W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(bytes)?;
+        if !self.indenting.get() {
+            if let Some(&last) = bytes[.. written].last() {
+                self.started.set(last != b'\n');
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer that forwards to `inner` with a `LinePrefix` at the start of
+/// every line. The prefix is written before the first byte of a line, so
+/// there is none after a line ending at the end of the output. The level
+/// passed to a `LinePrefix::Custom` is shared with a `StringifyContext`,
+/// as is `indenting`, which is set while the prefix is written since it
+/// counts as indentation for a `TrimWriter`.
+pub(crate) struct PrefixWriter<'w, 'p, W> {
+    inner: &'w mut W,
+    prefix: &'p LinePrefix,
+    level: Rc<Cell<usize>>,
+    indenting: Rc<Cell<bool>>,
+    /// The number of the line that has been started last.
+    line: usize,
+    at_line_start: bool,
+}
+
+impl<'w, 'p, W> PrefixWriter<'w, 'p, W>
+where W: Write {
+    pub(crate) fn new(inner: &'w mut W,
+                      prefix: &'p LinePrefix,
+                      level: Rc<Cell<usize>>,
+                      indenting: Rc<Cell<bool>>) -> Self {
+        Self { inner, prefix, level, indenting, line: 0, at_line_start: true }
+    }
+}
+
+impl<W> Write for PrefixWriter<'_, '_, W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let mut start = 0;
+        for (idx, &byte) in bytes.iter().enumerate() {
+            if self.at_line_start {
+                self.inner.write_all(&bytes[start .. idx])?;
+                start = idx;
+                self.line += 1;
+                let prefix = self.prefix.text(self.line, self.level.get());
+                let indenting = self.indenting.replace(true);
+                let result = self.inner.write_all(prefix.as_bytes());
+                self.indenting.set(indenting);
+                result?;
+                self.at_line_start = false;
+            }
+            if byte == b'\n' {
+                self.at_line_start = true;
+            }
+        }
+        self.inner.write_all(&bytes[start ..])?;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer that collects a single line, starting at `column`, for as long
+/// as it fits within `width` columns. After that, every write fails, which
+/// makes the stringification that is writing to it return early.
+pub(crate) struct FitWriter {
+    line: Vec<u8>,
+    columns: Columns,
+    width: usize,
+    overflowed: bool,
+}
+
+impl FitWriter {
+    pub(crate) fn new(column: usize, width: usize) -> Self {
+        let columns = Columns { column, escape: false };
+        Self { line: vec![], columns, width, overflowed: false }
+    }
+
+    /// The line that was written, if it fits.
+    pub(crate) fn into_line(self) -> Option<Vec<u8>> {
+        match self.overflowed {
+            true => None,
+            false => Some(self.line),
+        }
+    }
+}
+
+impl Write for FitWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let line_break = self.columns.advance(bytes);
+        if self.overflowed || line_break || self.columns.column > self.width {
+            self.overflowed = true;
+            return Err(io::Error::other("line width exceeded"));
+        }
+        self.line.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The number of columns that `text` takes up in a terminal: East Asian
+/// wide and fullwidth chars and emoji take up 2 columns, and combining
+/// marks, zero width chars, control chars and ANSI escape sequences none.
+pub(crate) fn display_width(text: &str) -> usize {
+    let mut escape = false;
+    text.chars().map(|c| match c {
+        _ if escape => {
+            escape = c == '[' || !('@' ..= '~').contains(&c);
+            0
+        },
+        '\x1b' => {
+            escape = true;
+            0
+        },
+        c => char_width(c),
+    }).sum()
+}
+
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x00 ..= 0x1f | 0x7f ..= 0x9f => 0,
+        0x0300 ..= 0x036f | 0x1ab0 ..= 0x1aff | 0x1dc0 ..= 0x1dff | 0x20d0 ..= 0x20ff => 0,
+        0x200b ..= 0x200f | 0xfe00 ..= 0xfe0f | 0xfe20 ..= 0xfe2f | 0xfeff => 0,
+        0x1100 ..= 0x115f | 0x2e80 ..= 0x303e | 0x3041 ..= 0x33ff | 0x3400 ..= 0x4dbf => 2,
+        0x4e00 ..= 0x9fff | 0xa000 ..= 0xa4cf | 0xac00 ..= 0xd7a3 | 0xf900 ..= 0xfaff => 2,
+        0xfe30 ..= 0xfe4f | 0xff00 ..= 0xff60 | 0xffe0 ..= 0xffe6 => 2,
+        0x1f300 ..= 0x1f64f | 0x1f900 ..= 0x1f9ff | 0x20000 ..= 0x3fffd => 2,
+        _ => 1,
+    }
+}
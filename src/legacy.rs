@@ -0,0 +1,462 @@
+//! The original `Stringify` trait, which writes to a `String` using four
+//! positional `Style`s. It is superseded by `Stringify2`, which is fallible,
+//! writes to any `io::Write` or `fmt::Write`, and is driven by `Styles`.
+#![allow(deprecated)]
+
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::newline::Newline;
+use crate::styles::{Style, StyleKey, Styles};
+use std::borrow::Cow;
+use std::collections::{HashMap};
+use std::hash::Hash;
+use std::io::Write;
+use std::rc::Rc;
+use std::sync::Arc;
+
+
+#[deprecated(note = "use `Stringify2`, which is fallible, writer-based and driven by `Styles`")]
+pub trait Stringify {
+    /// Stringify a datum. To achieve this, there are a number of
+    /// knobs that can be twisted to achieve the desired result:
+    /// - `parent_init` and `parent_rest` are the Styles used for `self`:
+    ///   + `parent_init` is used at the start of stringifying `self`
+    ///   + `parent_rest` is used everywhere else during the
+    ///     stringification of `self`
+    /// - `child_init` and `child_rest` are auxiliary Styles
+    ///   + `child_init` is used at the start of stringifying some
+    ///     internal component of `self`
+    ///   + `child_rest` is used everywhere else during the
+    ///     stringification of `self`
+    /// - `buffer` is the buffer to stringify to. In order to keep
+    ///   stringification as cheap as possible, a `&mut` to the buffer is
+    ///   passed in rather than allocating and returning the buffer.
+    ///
+    /// `Style` is not `Copy` since its `indent` can be owned, so a `Style`
+    /// that is passed on more than once has to be cloned.
+    fn stringify(&self,
+                 parent_init: Style,
+                 parent_rest: Style,
+                 child_init: Style,
+                 child_rest: Style,
+                 buffer: &mut String);
+
+    /// Convenience method that is an allocating version of `.stringify()`.
+    fn stringify_new(&self,
+                     parent_init: Style,
+                     parent_rest: Style,
+                     child_init: Style,
+                     child_rest: Style) -> String {
+        let mut buffer = String::new();
+        self.stringify(parent_init, parent_rest, child_init, child_rest, &mut buffer);
+        buffer
+    }
+
+    /// Convenience method to help stringify an enum variant / struct field.
+    fn stringify_field<V>(&self,
+                          name: &str,
+                          value: &V,
+                          name_style: Style,
+                          value_style: Style,
+                          buffer: &mut String)
+    where V: Stringify {
+        self.indent(name_style, buffer);
+        buffer.push_str(name);
+        buffer.push('=');
+        value.stringify(value_style.clone(), value_style.clone(), value_style.clone(), value_style, buffer);
+    }
+
+    fn stringify_primitive(&self, buffer: &mut String) {
+        self.stringify(
+            Style::default(), // unused
+            Style::default(), // unused
+            Style::default(), // unused
+            Style::default(), // unused
+            buffer
+        )
+    }
+
+    fn stringify_primitive_new(&self) -> String {
+        let mut buffer = String::new();
+        self.stringify_primitive(&mut buffer);
+        buffer
+    }
+
+    /// If `style.newline` == `Newline::Add`, write a newline (or as many
+    /// as `Newline::Count` asks for).
+    /// Then, regardless of whether or not a newline was written,
+    /// apply `style.indent` exactly `style.indent_level` times.
+    fn indent(&self, style: Style, buffer: &mut String) {
+        for _ in 0 .. style.newline.count() { buffer.push('\n'); }
+        for _ in 0 .. style.indent_level {
+            buffer.push_str(&style.indent);
+        }
+    }
+}
+
+/// An adapter that implements `Stringify2` for any type that only
+/// implements the legacy `Stringify`, e.g. `Legacy(&value).stringify_new(&styles)`.
+/// A blanket impl is not possible since many types implement both traits.
+///
+/// The four positional `Style`s are looked up in the `Styles`:
+/// - `parent_init` is `"start"`
+/// - `parent_rest` is `"end"`
+/// - `child_init` is `"child_init"`, falling back to `"start"`
+/// - `child_rest` is `"child_rest"`, falling back to `"end"`
+///
+/// The `Style`s that start a new line are shifted to the depth of `self`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Legacy<T>(pub T);
+
+// `Stringify2` is not imported here, since that would make the method calls
+// in the impls of `Stringify` below ambiguous.
+impl<T> crate::Stringify2 for Legacy<T>
+where T: Stringify {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let shift = |style: Style| match style.newline.count() {
+            0 => style,
+            _ => style + ctx.depth(),
+        };
+        let start = styles.get_or_default(StyleKey::Start);
+        let end = styles.get_or_default(StyleKey::End);
+        let child_init = shift(styles.get_or("child_init", start.clone()));
+        let child_rest = shift(styles.get_or("child_rest", end.clone()));
+        let parent_init = shift(start);
+        let parent_rest = shift(end);
+        let text = self.0.stringify_new(parent_init, parent_rest, child_init, child_rest);
+        buf.write_all(text.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl<K, V> Stringify for HashMap<K, V>
+where K: Stringify + Eq + Hash,
+      V: Stringify {
+    fn stringify(&self,
+                 parent_init: Style,
+                 parent_rest: Style,
+                 key_style: Style,
+                 value_style: Style,
+                 buffer: &mut String) {
+        if self.is_empty() {
+            buffer.push_str("HashMap {}");
+            return;
+        }
+        self.indent(parent_init, buffer);
+        buffer.push_str("HashMap {");
+        for (key, value) in self.iter() {
+            key.stringify(key_style.clone(), key_style.clone(), key_style.clone(), key_style.clone(), buffer);
+            buffer.push_str(" : ");
+            value.stringify(value_style.clone(), value_style.clone(), value_style.clone(), value_style.clone(), buffer);
+            buffer.push(',');
+        }
+        self.indent(Style::standard(Newline::Add, parent_rest.indent_level + 1), buffer);
+        buffer.push('}');
+    }
+}
+
+impl<T> Stringify for Vec<T>
+where T: Stringify {
+    fn stringify(&self,
+                 parent_init: Style,
+                 parent_rest: Style,
+                 elt_init: Style,
+                 elt_rest: Style,
+                 buffer: &mut String) {
+        self.indent(parent_init, buffer);
+        if self.is_empty() {
+            buffer.push_str("Vec []");
+            return;
+        }
+        buffer.push_str("Vec [");
+        for item in self.iter() {
+            self.indent(parent_rest.clone() + 1, buffer);
+            item.stringify(
+                elt_init.clone(),
+                elt_rest.clone(),
+                Style::default(), // unused
+                Style::default(), // unused
+                buffer
+            );
+            buffer.push(',');
+        }
+        self.indent(parent_rest, buffer);
+        buffer.push(']');
+    }
+}
+
+impl<T, E> Stringify for Result<T, E>
+where T: Stringify,
+      E: Stringify {
+    fn stringify(&self,
+                 parent_init: Style,
+                 parent_rest: Style,
+                 child_init: Style,
+                 child_rest: Style,
+                 buffer: &mut String) {
+        self.indent(parent_init.clone(), buffer);
+        match self {
+            Ok(ok) => {
+                buffer.push_str("Ok(");
+                ok.stringify(parent_init, parent_rest, child_init, child_rest, buffer);
+                buffer.push(')');
+            },
+            Err(err) => {
+                buffer.push_str("Err(");
+                err.stringify(parent_init, parent_rest, child_init, child_rest, buffer);
+                buffer.push(')');
+            },
+        }
+    }
+}
+
+impl<T> Stringify for Option<T>
+where T: Stringify {
+    fn stringify(&self,
+                 parent_init: Style,
+                 _parent_rest: Style,
+                 child_init: Style,
+                 child_rest: Style,
+                 buffer: &mut String) {
+        self.indent(parent_init, buffer);
+        match self {
+            Some(value) => {
+                buffer.push_str("Some(");
+                value.stringify(
+                    child_init,
+                    child_rest,
+                    Style::default(), // unused
+                    Style::default(), // unused
+                    buffer
+                );
+                buffer.push(')');
+            },
+            None => buffer.push_str("None"),
+        }
+    }
+}
+
+macro_rules! impl_stringify_for_tuples {
+    ($( ( $($name:ident $idx:tt),+ ) )+) => {
+        $(
+            impl<$($name),+> Stringify for ($($name,)+)
+            where $($name: Stringify),+ {
+                fn stringify(&self,
+                             parent_init: Style,
+                             parent_rest: Style,
+                             elt_init: Style,
+                             elt_rest: Style,
+                             buffer: &mut String) {
+                    self.indent(parent_init, buffer);
+                    buffer.push('(');
+                    $(
+                        self.indent(parent_rest.clone() + 1, buffer);
+                        self.$idx.stringify(
+                            elt_init.clone(),
+                            elt_rest.clone(),
+                            Style::default(), // unused
+                            Style::default(), // unused
+                            buffer
+                        );
+                        buffer.push(',');
+                    )+
+                    self.indent(parent_rest, buffer);
+                    buffer.push(')');
+                }
+            }
+        )+
+    };
+}
+
+impl_stringify_for_tuples! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+macro_rules! impl_stringify_for_pointers {
+    ($($ptr:ty),*) => {
+        $(
+            impl<T> Stringify for $ptr
+            where T: Stringify + ?Sized {
+                fn stringify(&self,
+                             parent_init: Style,
+                             parent_rest: Style,
+                             child_init: Style,
+                             child_rest: Style,
+                             buffer: &mut String) {
+                    (**self).stringify(parent_init, parent_rest, child_init, child_rest, buffer)
+                }
+            }
+        )*
+    };
+}
+
+impl_stringify_for_pointers!(&T, &mut T, Box<T>, Rc<T>, Arc<T>);
+
+impl<T> Stringify for Cow<'_, T>
+where T: Stringify + ToOwned + ?Sized {
+    fn stringify(&self,
+                 parent_init: Style,
+                 parent_rest: Style,
+                 child_init: Style,
+                 child_rest: Style,
+                 buffer: &mut String) {
+        (**self).stringify(parent_init, parent_rest, child_init, child_rest, buffer)
+    }
+}
+
+impl Stringify for bool {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for String {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(self);
+    }
+}
+
+impl Stringify for str {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(self);
+    }
+}
+
+impl Stringify for usize {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for u8 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for u16 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+impl Stringify for u32 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+impl Stringify for u64 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+impl Stringify for u128 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for isize {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for i8 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for i16 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+impl Stringify for i32 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+impl Stringify for i64 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+impl Stringify for i128 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for f32 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for f64 {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push_str(&format!("{}", self));
+    }
+}
+
+impl Stringify for char {
+    fn stringify(&self, _: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        buffer.push(*self);
+    }
+}
+
+
+
+impl Stringify for Style {
+    fn stringify(&self,
+                 parent_init: Style,
+                 parent_rest: Style,
+                 _child_init: Style,
+                 _child_rest: Style,
+                 buffer: &mut String) {
+        self.indent(parent_init, buffer);
+        buffer.push_str("Style {");
+
+        self.stringify_field(
+            "newline",
+            &self.newline,
+            Style { newline: Newline::Add,  indent_level: 0, indent: Cow::Borrowed(Style::INDENT) },
+            Style { newline: Newline::Omit, indent_level: 0, indent: Cow::Borrowed(Style::INDENT) },
+            buffer
+        );
+
+        self.stringify_field(
+            "indent_level",
+            &self.indent_level,
+            Style { newline: Newline::Add,  indent_level: 0, indent: Cow::Borrowed(Style::INDENT) },
+            Style { newline: Newline::Omit, indent_level: 0, indent: Cow::Borrowed(Style::INDENT) },
+            buffer
+        );
+
+        self.indent(parent_rest, buffer);
+        buffer.push('}');
+    }
+}
+
+impl Stringify for Newline {
+    fn stringify(&self, style: Style, _: Style, _: Style, _: Style, buffer: &mut String) {
+        self.indent(style, buffer);
+        buffer.push_str(&format!("Newline::{:?}", self));
+    }
+}
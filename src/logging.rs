@@ -0,0 +1,88 @@
+use crate::stringify::Stringify2;
+use crate::styles::Styles;
+use log::Level;
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+/// A value that is stringified with each line after the first one starting
+/// with a `prefix`, so that the lines of a multi-line value remain
+/// attributable in logs with interleaved records:
+/// ```text
+/// info!("{}", Prefixed::new(&config, &styles, "  | "));
+/// ```
+/// `log_stringified!` uses the level and target of the record as prefix.
+pub struct Prefixed<'a, T: ?Sized> {
+    value: &'a T,
+    styles: &'a Styles,
+    prefix: Cow<'a, str>,
+}
+
+impl<'a, T> Prefixed<'a, T>
+where T: Stringify2 + ?Sized {
+    pub fn new<P>(value: &'a T, styles: &'a Styles, prefix: P) -> Self
+    where P: Into<Cow<'a, str>> {
+        Self { value, styles, prefix: prefix.into() }
+    }
+}
+
+impl<T> fmt::Display for Prefixed<'_, T>
+where T: Stringify2 + ?Sized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut writer = PrefixWriter { inner: f, prefix: &self.prefix };
+        self.value.stringify_fmt(&mut writer, self.styles).map_err(|_| fmt::Error)
+    }
+}
+
+/// A `fmt::Write` that writes a `prefix` after each line break.
+struct PrefixWriter<'w, 'f> {
+    inner: &'w mut fmt::Formatter<'f>,
+    prefix: &'w str,
+}
+
+impl Write for PrefixWriter<'_, '_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for (idx, line) in text.split('\n').enumerate() {
+            if idx > 0 {
+                self.inner.write_char('\n')?;
+                self.inner.write_str(self.prefix)?;
+            }
+            self.inner.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The continuation prefix of the lines of a record with `level` and
+/// `target`, e.g. `INFO  my_crate::db | `.
+pub fn record_prefix(level: Level, target: &str) -> String {
+    format!("{:<5} {} | ", level, target)
+}
+
+/// Log a value with its continued lines prefixed by the level and target
+/// of the record (see `Prefixed` and `record_prefix`). The value is only
+/// stringified if the record is enabled. Without `Styles`, the `Styles`
+/// of the default `Stringifier` are used:
+/// ```text
+/// log_stringified!(Level::Info, &config);
+/// log_stringified!(target: "db", Level::Debug, &pool, &styles);
+/// ```
+#[macro_export]
+macro_rules! log_stringified {
+    (target: $target:expr, $level:expr, $value:expr, $styles:expr $(,)?) => {{
+        let (target, level) = ($target, $level);
+        if $crate::__log::log_enabled!(target: target, level) {
+            let prefix = $crate::record_prefix(level, target);
+            $crate::__log::log!(
+                target: target, level, "{}", $crate::Prefixed::new($value, $styles, prefix)
+            );
+        }
+    }};
+    (target: $target:expr, $level:expr, $value:expr $(,)?) => {
+        $crate::log_stringified!(
+            target: $target, $level, $value, $crate::Stringifier::default().styles()
+        )
+    };
+    ($level:expr, $value:expr $(, $styles:expr)? $(,)?) => {
+        $crate::log_stringified!(target: module_path!(), $level, $value $(, $styles)?)
+    };
+}
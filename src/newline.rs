@@ -1,5 +1,21 @@
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Newline {
     Add,
-    Omit
+    Omit,
+    /// Write the given number of newlines, e.g. `Newline::Count(2)` leaves
+    /// a blank line. `Newline::Count(1)` is the same as `Newline::Add`, and
+    /// `Newline::Count(0)` is the same as `Newline::Omit`.
+    Count(usize),
+}
+
+impl Newline {
+    /// The number of newlines written for `self`.
+    pub fn count(&self) -> usize {
+        match self {
+            Newline::Add => 1,
+            Newline::Omit => 0,
+            Newline::Count(count) => *count,
+        }
+    }
 }
@@ -0,0 +1,998 @@
+use crate::context::StringifyContext;
+use crate::error::{StringifyError, StringifyResult};
+use crate::options::{Layout, Options, Quoting};
+use crate::stringify::Stringify2;
+use crate::styles::Styles;
+use std::any::Any;
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// A format-independent description of a value, which `Stringify2` impls
+/// produce with `to_node`. It can be post-processed e.g. with `sort` and
+/// `retain_fields`, and then written in any format with a `Render`er.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Node {
+    /// The absence of a value, e.g. `None`.
+    Null,
+    Bool(bool),
+    /// A number as written by its `Display` impl, e.g. `-1.5`.
+    Number(String),
+    String(String),
+    Char(char),
+    /// The text written by a `Stringify2` impl that doesn't describe the
+    /// structure of its values, which is written as it is.
+    Text(String),
+    /// A unit struct or enum variant, e.g. `Auth::None`.
+    Unit(Cow<'static, str>),
+    /// A sequence, e.g. a `Vec`.
+    Seq { label: Cow<'static, str>, items: Vec<Node> },
+    /// A set, e.g. a `BTreeSet`, which is written with the set delimiters.
+    Set { label: Cow<'static, str>, items: Vec<Node> },
+    /// A tuple, tuple struct or tuple variant, e.g. `Some(1)`.
+    /// The label of a tuple is empty.
+    Tuple { label: Cow<'static, str>, items: Vec<Node> },
+    Map { label: Cow<'static, str>, entries: Vec<(Node, Node)> },
+    /// A struct or struct variant with named fields.
+    Struct { label: Cow<'static, str>, fields: Vec<(Cow<'static, str>, Node)> },
+}
+
+impl Node {
+    /// The node of `value`. If there is a formatter for the type of `value`
+    /// in the `Options` of `styles`, its output becomes a `Node::Text`.
+    pub fn of<T>(value: &T, styles: &Styles) -> StringifyResult<Node>
+    where T: Stringify2 + ?Sized {
+        let formatters = &styles.options().formatters;
+        if !formatters.is_empty() {
+            if let Some(text) = value.as_any().and_then(|any| formatters.format(any)) {
+                return Ok(Node::Text(text));
+            }
+        }
+        value.to_node(styles)
+    }
+
+    /// Sort the entries of all maps in `self` by their keys.
+    pub fn sort(&mut self) {
+        self.for_each_mut(&mut |node| {
+            if let Node::Map { entries, .. } = node {
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+        });
+    }
+
+    /// Remove the fields of all structs in `self` for which `keep`
+    /// returns `false`, given the name and the node of the field.
+    pub fn retain_fields<F>(&mut self, mut keep: F)
+    where F: FnMut(&str, &Node) -> bool {
+        self.for_each_mut(&mut |node| {
+            if let Node::Struct { fields, .. } = node {
+                fields.retain(|(name, node)| keep(name, node));
+            }
+        });
+    }
+
+    /// Call `f` on `self`, and then on each of the nodes that it contains.
+    fn for_each_mut<F>(&mut self, f: &mut F)
+    where F: FnMut(&mut Node) {
+        f(self);
+        match self {
+            Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. } =>
+                items.iter_mut().for_each(|item| item.for_each_mut(f)),
+            Node::Map { entries, .. } => entries.iter_mut().for_each(|(key, value)| {
+                key.for_each_mut(f);
+                value.for_each_mut(f);
+            }),
+            Node::Struct { fields, .. } =>
+                fields.iter_mut().for_each(|(_, node)| node.for_each_mut(f)),
+            _ => {},
+        }
+    }
+
+    /// Add the field `name` with node `self` to `fields`, or if `self` is
+    /// a struct, its fields, as `#[stringify(flatten)]` does when deriving.
+    #[doc(hidden)]
+    pub fn __flatten_into(self,
+                          name: &'static str,
+                          fields: &mut Vec<(Cow<'static, str>, Node)>) {
+        match self {
+            Node::Struct { fields: flattened, .. } => fields.extend(flattened),
+            node => fields.push((Cow::Borrowed(name), node)),
+        }
+    }
+}
+
+/// A `Node` is written like the value it describes, except that numbers are
+/// written as they are, apart from the `Radix` and `Grouping` of integers.
+impl Stringify2 for Node {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        crate::stringify::stringify_node(self, buf, styles, ctx)
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(self.clone())
+    }
+
+    fn is_unset(&self) -> bool {
+        match self {
+            Node::Null => true,
+            Node::Bool(value) => !value,
+            Node::Number(number) => number.parse::<f64>().is_ok_and(|number| number == 0.0),
+            Node::String(text) => text.is_empty(),
+            Node::Seq { items, .. } | Node::Set { items, .. } => items.is_empty(),
+            Node::Map { entries, .. } => entries.is_empty(),
+            Node::Struct { fields, .. } => {
+                !fields.is_empty() && fields.iter().all(|(_, value)| value.is_unset())
+            },
+            _ => false,
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/// A way of writing a `Node`. Renderers are object safe, so that the
+/// format can be chosen at runtime e.g. with a `Box<dyn Render>`.
+pub trait Render {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()>;
+
+    fn render_to_string(&self, node: &Node) -> StringifyResult<String> {
+        let mut buf = vec![];
+        self.render(node, &mut buf)?;
+        String::from_utf8(buf).map_err(|_| StringifyError::InvalidUtf8 { path: String::new() })
+    }
+}
+
+/// Renders a `Node` like `Stringify2` does, with the given `Styles`.
+#[derive(Clone, Debug, Default)]
+pub struct Pretty(pub Styles);
+
+impl Render for Pretty {
+    fn render(&self, node: &Node, mut buf: &mut dyn Write) -> StringifyResult<()> {
+        node.stringify_root(&mut buf, &self.0)
+    }
+}
+
+/// Renders a `Node` on a single line with `Layout::Compact`.
+#[derive(Clone, Debug, Default)]
+pub struct Compact(pub Styles);
+
+impl Render for Compact {
+    fn render(&self, node: &Node, mut buf: &mut dyn Write) -> StringifyResult<()> {
+        let options = self.0.options().clone().with_layout(Layout::Compact);
+        node.stringify_root(&mut buf, &self.0.clone().with_options(options))
+    }
+}
+
+/// Renders a `Node` as JSON. Sequences and tuples become arrays, except
+/// that a tuple with a single item, e.g. `Some(1)`, becomes that item.
+/// Maps and structs become objects, where map keys that aren't strings
+/// are written as strings. Labels are left out, unit variants become
+/// strings, and numbers that JSON can't represent e.g. `NaN` become `null`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Json {
+    /// The indentation unit of nested values, or `None` to write
+    /// everything on a single line.
+    pub indent: Option<Cow<'static, str>>,
+}
+
+impl Json {
+    /// JSON on a single line.
+    pub fn compact() -> Self {
+        Self { indent: None }
+    }
+
+    /// JSON with one member or element per line, indented by 2 spaces.
+    pub fn pretty() -> Self {
+        Self { indent: Some(Cow::Borrowed("  ")) }
+    }
+
+    fn write<W>(&self, node: &Node, buf: &mut W, level: usize) -> io::Result<()>
+    where W: Write + ?Sized {
+        match node {
+            Node::Null => buf.write_all(b"null"),
+            Node::Bool(value) => write!(buf, "{}", value),
+            Node::Number(number) if is_json_number(number) => buf.write_all(number.as_bytes()),
+            Node::Number(_) => buf.write_all(b"null"),
+            Node::String(text) | Node::Text(text) => write_json_string(buf, text),
+            Node::Char(c) => write_json_string(buf, c.encode_utf8(&mut [0; 4])),
+            Node::Unit(label) => write_json_string(buf, variant_name(label)),
+            Node::Tuple { items, .. } if items.len() == 1 => self.write(&items[0], buf, level),
+            Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. } => {
+                let indent = self.indent.as_deref();
+                write_members(buf, indent, level, "[", "]", false, items, |item, buf, level| {
+                    self.write(item, buf, level)
+                })
+            },
+            Node::Map { entries, .. } => {
+                let indent = self.indent.as_deref();
+                write_members(buf, indent, level, "{", "}", false, entries, |(key, value), buf, level| {
+                    write_json_string(buf, &key_text(key)?)?;
+                    buf.write_all(self.colon().as_bytes())?;
+                    self.write(value, buf, level)
+                })
+            },
+            Node::Struct { fields, .. } => {
+                let indent = self.indent.as_deref();
+                write_members(buf, indent, level, "{", "}", false, fields, |(name, value), buf, level| {
+                    write_json_string(buf, name)?;
+                    buf.write_all(self.colon().as_bytes())?;
+                    self.write(value, buf, level)
+                })
+            },
+        }
+    }
+
+    fn colon(&self) -> &'static str {
+        match self.indent {
+            Some(_) => ": ",
+            None => ":",
+        }
+    }
+}
+
+impl Render for Json {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        self.write(node, buf, 0)?;
+        Ok(())
+    }
+}
+
+/// Write the `members` of a container between `open` and `close`, one per
+/// line indented by `indent` if there is one, with a comma after the last
+/// member too if `trailing` is `true` and the members are on their own lines.
+#[allow(clippy::too_many_arguments)]
+fn write_members<W, T, F>(buf: &mut W,
+                          indent: Option<&str>,
+                          level: usize,
+                          open: &str,
+                          close: &str,
+                          trailing: bool,
+                          members: &[T],
+                          write_member: F) -> io::Result<()>
+where W: Write + ?Sized,
+      F: Fn(&T, &mut W, usize) -> io::Result<()> {
+    buf.write_all(open.as_bytes())?;
+    for (idx, member) in members.iter().enumerate() {
+        if idx > 0 {
+            buf.write_all(b",")?;
+        }
+        write_indented_line(buf, indent, level + 1)?;
+        write_member(member, buf, level + 1)?;
+    }
+    if !members.is_empty() {
+        if trailing && indent.is_some() {
+            buf.write_all(b",")?;
+        }
+        write_indented_line(buf, indent, level)?;
+    }
+    buf.write_all(close.as_bytes())
+}
+
+/// Start a new line at indentation `level`, unless there is no `indent`.
+fn write_indented_line<W>(buf: &mut W, indent: Option<&str>, level: usize) -> io::Result<()>
+where W: Write + ?Sized {
+    if let Some(indent) = indent {
+        buf.write_all(b"\n")?;
+        for _ in 0 .. level {
+            buf.write_all(indent.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// The text of a map `key` for formats in which keys are strings. Keys that
+/// are neither scalars nor unit variants are written on a single line.
+pub(crate) fn key_text(key: &Node) -> io::Result<Cow<'_, str>> {
+    Ok(match key {
+        Node::String(text) | Node::Text(text) | Node::Number(text) => Cow::Borrowed(text),
+        Node::Char(c) => Cow::Owned(c.to_string()),
+        Node::Bool(value) => Cow::Owned(value.to_string()),
+        Node::Null => Cow::Borrowed("null"),
+        Node::Unit(label) => Cow::Borrowed(variant_name(label)),
+        key => Cow::Owned(Compact::default().render_to_string(key)
+            .map_err(|_| io::Error::other("map key can't be written"))?),
+    })
+}
+
+/// Return `true` if `number` is a valid JSON number.
+fn is_json_number(number: &str) -> bool {
+    let digits = number.strip_prefix('-').unwrap_or(number);
+    digits.starts_with(|c: char| c.is_ascii_digit())
+        && number.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// The name of a unit variant without the name of its enum,
+/// e.g. `None` for `Auth::None`.
+fn variant_name(label: &str) -> &str {
+    label.rsplit("::").next().unwrap_or(label)
+}
+
+fn write_json_string<W>(buf: &mut W, text: &str) -> io::Result<()>
+where W: Write + ?Sized {
+    buf.write_all(b"\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => buf.write_all(b"\\\"")?,
+            '\\' => buf.write_all(b"\\\\")?,
+            '\n' => buf.write_all(b"\\n")?,
+            '\r' => buf.write_all(b"\\r")?,
+            '\t' => buf.write_all(b"\\t")?,
+            c if (c as u32) < 0x20 => write!(buf, "\\u{:04x}", c as u32)?,
+            c => write!(buf, "{}", c)?,
+        }
+    }
+    buf.write_all(b"\"")
+}
+
+/// Renders a `Node` as YAML, with the same mapping as `Json`.
+/// Strings are only quoted when they would otherwise be read back
+/// as something else.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Yaml {
+    /// The indentation unit of nested values.
+    pub indent: Cow<'static, str>,
+}
+
+impl Default for Yaml {
+    fn default() -> Self {
+        Self { indent: Cow::Borrowed("  ") }
+    }
+}
+
+impl Yaml {
+    /// Write `node`, which starts on the current line at indentation `level`.
+    fn write<W>(&self, node: &Node, buf: &mut W, level: usize) -> io::Result<()>
+    where W: Write + ?Sized {
+        match node {
+            Node::Tuple { items, .. } if items.len() == 1 => self.write(&items[0], buf, level),
+            Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. }
+                if !items.is_empty() => {
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        self.write_line(buf, level)?;
+                    }
+                    buf.write_all(b"- ")?;
+                    self.write(item, buf, level + 1)?;
+                }
+                Ok(())
+            },
+            Node::Seq { .. } | Node::Set { .. } | Node::Tuple { .. } => buf.write_all(b"[]"),
+            Node::Map { entries, .. } if !entries.is_empty() => {
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        self.write_line(buf, level)?;
+                    }
+                    self.write_member(buf, level, &key_text(key)?, value)?;
+                }
+                Ok(())
+            },
+            Node::Struct { fields, .. } if !fields.is_empty() => {
+                for (idx, (name, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        self.write_line(buf, level)?;
+                    }
+                    self.write_member(buf, level, name, value)?;
+                }
+                Ok(())
+            },
+            Node::Map { .. } | Node::Struct { .. } => buf.write_all(b"{}"),
+            Node::Null => buf.write_all(b"null"),
+            Node::Bool(value) => write!(buf, "{}", value),
+            Node::Number(number) if is_json_number(number) => buf.write_all(number.as_bytes()),
+            Node::Number(number) => match number.as_str() {
+                "inf" => buf.write_all(b".inf"),
+                "-inf" => buf.write_all(b"-.inf"),
+                _ => buf.write_all(b".nan"),
+            },
+            Node::String(text) | Node::Text(text) => write_yaml_string(buf, text),
+            Node::Char(c) => write_yaml_string(buf, c.encode_utf8(&mut [0; 4])),
+            Node::Unit(label) => write_yaml_string(buf, variant_name(label)),
+        }
+    }
+
+    /// Write the mapping member `name: value`. Values that are non-empty
+    /// containers start on the next line, one level deeper.
+    fn write_member<W>(&self,
+                       buf: &mut W,
+                       level: usize,
+                       name: &str,
+                       value: &Node) -> io::Result<()>
+    where W: Write + ?Sized {
+        write_yaml_string(buf, name)?;
+        buf.write_all(b":")?;
+        if is_yaml_block(value) {
+            self.write_line(buf, level + 1)?;
+            self.write(value, buf, level + 1)
+        } else {
+            buf.write_all(b" ")?;
+            self.write(value, buf, level + 1)
+        }
+    }
+
+    fn write_line<W>(&self, buf: &mut W, level: usize) -> io::Result<()>
+    where W: Write + ?Sized {
+        buf.write_all(b"\n")?;
+        for _ in 0 .. level {
+            buf.write_all(self.indent.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl Render for Yaml {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        self.write(node, buf, 0)?;
+        Ok(())
+    }
+}
+
+/// Return `true` if `node` is written as a block of lines in YAML.
+fn is_yaml_block(node: &Node) -> bool {
+    match node {
+        Node::Tuple { items, .. } if items.len() == 1 => is_yaml_block(&items[0]),
+        Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. } => !items.is_empty(),
+        Node::Map { entries, .. } => !entries.is_empty(),
+        Node::Struct { fields, .. } => !fields.is_empty(),
+        _ => false,
+    }
+}
+
+/// Write `text` as a plain scalar if that reads back as the same string,
+/// and as a double-quoted scalar otherwise.
+fn write_yaml_string<W>(buf: &mut W, text: &str) -> io::Result<()>
+where W: Write + ?Sized {
+    let reserved = ["", "~", "null", "Null", "NULL", "true", "True", "TRUE",
+                    "false", "False", "FALSE", "yes", "no", "on", "off"];
+    let plain = !reserved.contains(&text)
+        && text.parse::<f64>().is_err()
+        && text.chars().all(|c| c.is_alphanumeric() || " _-./()".contains(c))
+        && !text.starts_with([' ', '-'])
+        && !text.ends_with(' ');
+    match plain {
+        true => buf.write_all(text.as_bytes()),
+        false => write_json_string(buf, text),
+    }
+}
+
+/// Renders a `Node` as RON (Rusty Object Notation), which reads like Rust:
+/// structs and tuple structs are written with their names, e.g.
+/// `Server(host: "a", port: 80)`, variants with the name of the variant,
+/// tuples between parentheses, and `None` and `Some(1)` as they are.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ron {
+    /// The indentation unit of nested values, or `None` to write
+    /// everything on a single line.
+    pub indent: Option<Cow<'static, str>>,
+}
+
+impl Ron {
+    /// RON on a single line.
+    pub fn compact() -> Self {
+        Self { indent: None }
+    }
+
+    /// RON with one member or element per line, indented by 4 spaces
+    /// and followed by a comma.
+    pub fn pretty() -> Self {
+        Self { indent: Some(Cow::Borrowed("    ")) }
+    }
+
+    fn write<W>(&self, node: &Node, buf: &mut W, level: usize) -> io::Result<()>
+    where W: Write + ?Sized {
+        let indent = self.indent.as_deref();
+        let trailing = indent.is_some();
+        match node {
+            Node::Null => buf.write_all(b"None"),
+            Node::Bool(value) => write!(buf, "{}", value),
+            Node::Number(number) => match number.as_str() {
+                "NaN" | "-NaN" => buf.write_all(b"NaN"),
+                number => buf.write_all(number.as_bytes()),
+            },
+            Node::String(text) | Node::Text(text) => write_json_string(buf, text),
+            Node::Char(c) => write_ron_char(buf, *c),
+            Node::Unit(label) => buf.write_all(variant_name(label).as_bytes()),
+            Node::Seq { items, .. } | Node::Set { items, .. } => {
+                write_members(buf, indent, level, "[", "]", trailing, items, |item, buf, level| {
+                    self.write(item, buf, level)
+                })
+            },
+            Node::Tuple { label, items } => {
+                buf.write_all(variant_name(label).as_bytes())?;
+                write_members(buf, indent, level, "(", ")", trailing, items, |item, buf, level| {
+                    self.write(item, buf, level)
+                })
+            },
+            Node::Map { entries, .. } => {
+                write_members(buf, indent, level, "{", "}", trailing, entries, |(key, value), buf, level| {
+                    self.write(key, buf, level)?;
+                    buf.write_all(self.colon().as_bytes())?;
+                    self.write(value, buf, level)
+                })
+            },
+            Node::Struct { label, fields } => {
+                buf.write_all(variant_name(label).as_bytes())?;
+                write_members(buf, indent, level, "(", ")", trailing, fields, |(name, value), buf, level| {
+                    write!(buf, "{}{}", name, self.colon())?;
+                    self.write(value, buf, level)
+                })
+            },
+        }
+    }
+
+    fn colon(&self) -> &'static str {
+        match self.indent {
+            Some(_) => ": ",
+            None => ":",
+        }
+    }
+}
+
+impl Render for Ron {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        self.write(node, buf, 0)?;
+        Ok(())
+    }
+}
+
+fn write_ron_char<W>(buf: &mut W, c: char) -> io::Result<()>
+where W: Write + ?Sized {
+    match c {
+        '"' => buf.write_all(b"'\"'"),
+        c => write!(buf, "'{}'", c.escape_debug()),
+    }
+}
+
+/// Renders a `Node` as nested Markdown bullet lists, e.g. for pasting into
+/// an issue or a wiki page. Struct fields are written in bold, followed by
+/// their value if it is a scalar, or by the label of the container with its
+/// members in a nested list otherwise. Scalars are written as inline text,
+/// with the characters that Markdown would interpret escaped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Markdown {
+    /// The indentation unit of nested lists.
+    pub indent: Cow<'static, str>,
+}
+
+impl Default for Markdown {
+    fn default() -> Self {
+        Self { indent: Cow::Borrowed("  ") }
+    }
+}
+
+impl Markdown {
+    /// Write the members of the container `node` as list items at
+    /// indentation `level`, or `node` itself if it is a scalar.
+    fn write<W>(&self, node: &Node, buf: &mut W, level: usize) -> io::Result<()>
+    where W: Write + ?Sized {
+        match node {
+            Node::Tuple { items, .. } if items.len() == 1 => self.write(&items[0], buf, level),
+            Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. }
+                if !items.is_empty() => {
+                for (idx, item) in items.iter().enumerate() {
+                    self.write_item(buf, level, idx, None, item)?;
+                }
+                Ok(())
+            },
+            Node::Map { entries, .. } if !entries.is_empty() => {
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    let mut name = String::new();
+                    write_markdown_text(&mut name, &key_text(key)?);
+                    self.write_item(buf, level, idx, Some(&name), value)?;
+                }
+                Ok(())
+            },
+            Node::Struct { fields, .. } if !fields.is_empty() => {
+                for (idx, (field, value)) in fields.iter().enumerate() {
+                    let mut name = String::from("**");
+                    write_markdown_text(&mut name, field);
+                    name.push_str("**");
+                    self.write_item(buf, level, idx, Some(&name), value)?;
+                }
+                Ok(())
+            },
+            node => buf.write_all(markdown_scalar(node).as_bytes()),
+        }
+    }
+
+    /// Write the list item `- name: value`, or `- value` if the item
+    /// has no `name`. Containers are written as their label, with their
+    /// members in a nested list one level deeper.
+    fn write_item<W>(&self,
+                     buf: &mut W,
+                     level: usize,
+                     idx: usize,
+                     name: Option<&str>,
+                     value: &Node) -> io::Result<()>
+    where W: Write + ?Sized {
+        if idx > 0 {
+            buf.write_all(b"\n")?;
+        }
+        for _ in 0 .. level {
+            buf.write_all(self.indent.as_bytes())?;
+        }
+        buf.write_all(b"- ")?;
+        if let Some(name) = name {
+            buf.write_all(name.as_bytes())?;
+            buf.write_all(b":")?;
+        }
+        if !is_markdown_list(value) {
+            if name.is_some() {
+                buf.write_all(b" ")?;
+            }
+            return buf.write_all(markdown_scalar(value).as_bytes());
+        }
+        match container_label(value) {
+            "" if name.is_none() => write!(buf, "[{}]", idx)?,
+            "" => {},
+            label => {
+                if name.is_some() {
+                    buf.write_all(b" ")?;
+                }
+                buf.write_all(markdown_scalar(&Node::Text(label.to_string())).as_bytes())?;
+            },
+        }
+        buf.write_all(b"\n")?;
+        self.write(value, buf, level + 1)
+    }
+}
+
+impl Render for Markdown {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        self.write(node, buf, 0)?;
+        Ok(())
+    }
+}
+
+/// Return `true` if `node` is written as a nested list in Markdown.
+fn is_markdown_list(node: &Node) -> bool {
+    match node {
+        Node::Tuple { items, .. } if items.len() == 1 => is_markdown_list(&items[0]),
+        node => is_yaml_block(node),
+    }
+}
+
+/// The label of the container `node`, or of the item of a tuple with a
+/// single item.
+fn container_label(node: &Node) -> &str {
+    match node {
+        Node::Tuple { items, .. } if items.len() == 1 => container_label(&items[0]),
+        Node::Seq { label, .. } | Node::Set { label, .. } | Node::Tuple { label, .. }
+            | Node::Map { label, .. } | Node::Struct { label, .. } => label,
+        _ => "",
+    }
+}
+
+/// The inline Markdown text of the scalar `node`.
+fn markdown_scalar(node: &Node) -> String {
+    let mut text = String::new();
+    match node {
+        Node::Tuple { items, .. } if items.len() == 1 => return markdown_scalar(&items[0]),
+        Node::Null => text.push_str("null"),
+        Node::Bool(value) => text.push_str(&value.to_string()),
+        Node::Number(number) => text.push_str(number),
+        Node::String(string) | Node::Text(string) => write_markdown_text(&mut text, string),
+        Node::Char(c) => write_markdown_text(&mut text, c.encode_utf8(&mut [0; 4])),
+        Node::Unit(label) => write_markdown_text(&mut text, label),
+        Node::Seq { .. } | Node::Tuple { .. } => text.push_str("[]"),
+        Node::Set { .. } | Node::Map { .. } | Node::Struct { .. } => text.push_str("{}"),
+    }
+    text
+}
+
+/// Append `text` to `buf`, escaping the characters that Markdown would
+/// interpret, and writing line breaks as `<br>`.
+fn write_markdown_text(buf: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' => {
+                buf.push('\\');
+                buf.push(c);
+            },
+            '\n' => buf.push_str("<br>"),
+            '\r' => {},
+            c => buf.push(c),
+        }
+    }
+}
+
+/// Renders a `Node` as an HTML fragment for e.g. web dashboards. Containers
+/// become lists of their members, wrapped in a `<details>` element that can
+/// be collapsed if `collapsible` is `true`. Labels, field names, map keys
+/// and scalars are wrapped in `<span>` elements with a class for each kind,
+/// e.g. `stringify-field` and `stringify-number`, to style them with CSS.
+/// All text is escaped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Html {
+    /// The prefix of the CSS classes, e.g. `stringify-` for `stringify-field`.
+    pub class_prefix: Cow<'static, str>,
+    /// Whether containers are written as `<details open>` elements with the
+    /// label as `<summary>`, rather than as `<div>` elements.
+    pub collapsible: bool,
+}
+
+impl Default for Html {
+    fn default() -> Self {
+        Self { class_prefix: Cow::Borrowed("stringify-"), collapsible: true }
+    }
+}
+
+impl Html {
+    fn write<W>(&self, node: &Node, buf: &mut W) -> io::Result<()>
+    where W: Write + ?Sized {
+        match node {
+            Node::Null => self.write_span(buf, "null", "None"),
+            Node::Bool(value) => self.write_span(buf, "bool", &value.to_string()),
+            Node::Number(number) => self.write_span(buf, "number", number),
+            Node::String(text) => self.write_span(buf, "string", &format!("{:?}", text)),
+            Node::Char(c) => self.write_span(buf, "string", &format!("{:?}", c)),
+            Node::Text(text) => self.write_span(buf, "text", text),
+            Node::Unit(label) => self.write_span(buf, "unit", label),
+            Node::Seq { label, items } => {
+                self.write_container(buf, "seq", label, items, |item, buf| self.write(item, buf))
+            },
+            Node::Set { label, items } => {
+                self.write_container(buf, "set", label, items, |item, buf| self.write(item, buf))
+            },
+            Node::Tuple { label, items } => {
+                self.write_container(buf, "tuple", label, items, |item, buf| self.write(item, buf))
+            },
+            Node::Map { label, entries } => {
+                self.write_container(buf, "map", label, entries, |(key, value), buf| {
+                    write!(buf, r#"<span class="{}key">"#, self.class_prefix)?;
+                    self.write(key, buf)?;
+                    buf.write_all(b"</span>: ")?;
+                    self.write(value, buf)
+                })
+            },
+            Node::Struct { label, fields } => {
+                self.write_container(buf, "struct", label, fields, |(name, value), buf| {
+                    self.write_span(buf, "field", name)?;
+                    buf.write_all(b": ")?;
+                    self.write(value, buf)
+                })
+            },
+        }
+    }
+
+    /// Write the `members` of a container of the given `kind` as list
+    /// items, after its `label`. Empty containers are written as a label.
+    fn write_container<W, T, F>(&self,
+                                buf: &mut W,
+                                kind: &str,
+                                label: &str,
+                                members: &[T],
+                                write_member: F) -> io::Result<()>
+    where W: Write + ?Sized,
+          F: Fn(&T, &mut W) -> io::Result<()> {
+        let prefix = &self.class_prefix;
+        if members.is_empty() {
+            write!(buf, r#"<span class="{}{}">"#, prefix, kind)?;
+            if !label.is_empty() {
+                self.write_span(buf, "label", label)?;
+                buf.write_all(b" ")?;
+            }
+            buf.write_all(match kind {
+                "map" | "set" | "struct" => b"{}",
+                "tuple" => b"()",
+                _ => b"[]",
+            })?;
+            return buf.write_all(b"</span>");
+        }
+        let (element, summary) = match self.collapsible {
+            true => ("details", "summary"),
+            false => ("div", "span"),
+        };
+        let open = if self.collapsible { " open" } else { "" };
+        write!(buf, r#"<{} class="{}{}"{}><{}>"#, element, prefix, kind, open, summary)?;
+        self.write_span(buf, "label", label)?;
+        write!(buf, "</{}><ul>", summary)?;
+        for member in members {
+            buf.write_all(b"<li>")?;
+            write_member(member, buf)?;
+            buf.write_all(b"</li>")?;
+        }
+        write!(buf, "</ul></{}>", element)
+    }
+
+    /// Write `text`, escaped, in a `<span>` with the class of `kind`.
+    fn write_span<W>(&self, buf: &mut W, kind: &str, text: &str) -> io::Result<()>
+    where W: Write + ?Sized {
+        write!(buf, r#"<span class="{}{}">"#, self.class_prefix, kind)?;
+        write_html_text(buf, text)?;
+        buf.write_all(b"</span>")
+    }
+}
+
+impl Render for Html {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        self.write(node, buf)?;
+        Ok(())
+    }
+}
+
+fn write_html_text<W>(buf: &mut W, text: &str) -> io::Result<()>
+where W: Write + ?Sized {
+    for c in text.chars() {
+        match c {
+            '&' => buf.write_all(b"&amp;")?,
+            '<' => buf.write_all(b"&lt;")?,
+            '>' => buf.write_all(b"&gt;")?,
+            '"' => buf.write_all(b"&quot;")?,
+            '\'' => buf.write_all(b"&#39;")?,
+            c => write!(buf, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Renders a `Node` as a Graphviz DOT graph, e.g. to visualize the structure
+/// of deeply nested state with `dot -Tsvg`. Containers become boxes labelled
+/// with their label, and scalars become plain text. Each member of a
+/// container becomes an edge to its value, labelled with its field name,
+/// map key or index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dot {
+    /// The name of the graph.
+    pub name: Cow<'static, str>,
+}
+
+impl Default for Dot {
+    fn default() -> Self {
+        Self { name: Cow::Borrowed("stringify") }
+    }
+}
+
+impl Dot {
+    /// Write the graph node of `node` and the edges to its members, and
+    /// return its id. Ids are numbered in the order they are written.
+    fn write<W>(&self, node: &Node, buf: &mut W, next_id: &mut usize) -> io::Result<usize>
+    where W: Write + ?Sized {
+        let id = *next_id;
+        *next_id += 1;
+        let (label, shape) = match node {
+            Node::Seq { label, .. } | Node::Set { label, .. } | Node::Tuple { label, .. }
+                | Node::Map { label, .. } | Node::Struct { label, .. } => {
+                let label = match (label.as_ref(), node) {
+                    ("", Node::Tuple { .. }) => "()",
+                    ("", Node::Seq { .. }) => "[]",
+                    ("", _) => "{}",
+                    (label, _) => label,
+                };
+                (Cow::Borrowed(label), "box")
+            },
+            scalar => (Cow::Owned(Compact::default().render_to_string(scalar)
+                .map_err(|_| io::Error::other("scalar can't be written"))?), "plaintext"),
+        };
+        write!(buf, "    n{} [shape={}, label=", id, shape)?;
+        write_json_string(buf, &label)?;
+        buf.write_all(b"];\n")?;
+        match node {
+            Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. } => {
+                for (idx, item) in items.iter().enumerate() {
+                    self.write_edge(buf, id, &idx.to_string(), item, next_id)?;
+                }
+            },
+            Node::Map { entries, .. } => {
+                for (key, value) in entries {
+                    self.write_edge(buf, id, &key_text(key)?, value, next_id)?;
+                }
+            },
+            Node::Struct { fields, .. } => {
+                for (name, value) in fields {
+                    self.write_edge(buf, id, name, value, next_id)?;
+                }
+            },
+            _ => {},
+        }
+        Ok(id)
+    }
+
+    /// Write the graph node of `node`, and an edge labelled `label`
+    /// to it from the graph node `from`.
+    fn write_edge<W>(&self,
+                     buf: &mut W,
+                     from: usize,
+                     label: &str,
+                     node: &Node,
+                     next_id: &mut usize) -> io::Result<()>
+    where W: Write + ?Sized {
+        let to = self.write(node, buf, next_id)?;
+        write!(buf, "    n{} -> n{} [label=", from, to)?;
+        write_json_string(buf, label)?;
+        buf.write_all(b"];\n")
+    }
+}
+
+impl Render for Dot {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        buf.write_all(b"digraph ")?;
+        write_json_string(buf, &self.name)?;
+        buf.write_all(b" {\n")?;
+        self.write(node, buf, &mut 0)?;
+        buf.write_all(b"}\n")?;
+        Ok(())
+    }
+}
+
+/// Renders a `Node` as a tree with `├──`, `└──` and `│` connectors instead
+/// of delimiters, like `cargo tree`. Each member of a container is written
+/// on a line of its own below its container, after its field name or map
+/// key. Scalars, empty containers and tuples of scalars, e.g. `Some(1)`,
+/// are written on a single line, with strings quoted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tree {
+    /// Whether the connectors are drawn with ASCII chars, i.e. `|--`,
+    /// `` `-- `` and `|`, rather than with box-drawing chars.
+    pub ascii: bool,
+}
+
+impl Tree {
+    /// Write `node` after the connector of its line, and its members on the
+    /// lines below it. `ancestors` holds whether each of the containers
+    /// that `node` is nested in is the last member of its own container,
+    /// which determines whether a `│` continues in the column of each one.
+    fn write<W>(&self, node: &Node, buf: &mut W, ancestors: &mut Vec<bool>) -> io::Result<()>
+    where W: Write + ?Sized {
+        let members: Vec<(Cow<str>, &Node)> = match node {
+            _ if is_tree_leaf(node) => {
+                // Strings are quoted, so that their line breaks are escaped.
+                let options = Options::default().with_quoting(Quoting::Quoted);
+                let text = Compact(Styles::default().with_options(options)).render_to_string(node)
+                    .map_err(|_| io::Error::other("tree leaf can't be written"))?;
+                return buf.write_all(text.as_bytes());
+            },
+            Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. } =>
+                items.iter().map(|item| (Cow::Borrowed(""), item)).collect(),
+            Node::Map { entries, .. } => entries.iter()
+                .map(|(key, value)| Ok((key_text(key)?, value)))
+                .collect::<io::Result<_>>()?,
+            Node::Struct { fields, .. } =>
+                fields.iter().map(|(name, value)| (Cow::Borrowed(name.as_ref()), value)).collect(),
+            _ => vec![],
+        };
+        buf.write_all(match node {
+            Node::Seq { label, .. } | Node::Set { label, .. } | Node::Tuple { label, .. }
+                | Node::Map { label, .. } | Node::Struct { label, .. } => label.as_bytes(),
+            _ => b"",
+        })?;
+        let (branch, last_branch, rail, space) = match self.ascii {
+            true => ("|-- ", "`-- ", "|   ", "    "),
+            false => ("├── ", "└── ", "│   ", "    "),
+        };
+        for (idx, (name, member)) in members.iter().enumerate() {
+            let last = idx + 1 == members.len();
+            buf.write_all(b"\n")?;
+            for &ancestor_last in ancestors.iter() {
+                buf.write_all(if ancestor_last { space } else { rail }.as_bytes())?;
+            }
+            buf.write_all(if last { last_branch } else { branch }.as_bytes())?;
+            if !name.is_empty() {
+                write!(buf, "{}: ", name)?;
+            }
+            ancestors.push(last);
+            self.write(member, buf, ancestors)?;
+            ancestors.pop();
+        }
+        Ok(())
+    }
+}
+
+impl Render for Tree {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        self.write(node, buf, &mut vec![])?;
+        Ok(())
+    }
+}
+
+/// Return `true` if `node` is written on a single line in a `Tree`.
+fn is_tree_leaf(node: &Node) -> bool {
+    match node {
+        Node::Tuple { items, .. } => items.iter().all(|item| !is_yaml_block(item)),
+        node => !is_yaml_block(node),
+    }
+}
@@ -0,0 +1,866 @@
+use crate::styles::Style;
+use crate::theme::Theme;
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Formatting knobs that apply to a whole stringification rather than to
+/// a single named `Style`. They are carried along by `Styles`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Options {
+    /// The policy for rendering `Option::None`.
+    pub none: NoneStyle,
+
+    /// The formatting of numbers.
+    pub number: NumberFormat,
+
+    /// The policy for quoting strings and chars.
+    pub quoting: Quoting,
+
+    /// The policy for rendering unquoted strings that contain newlines.
+    pub multiline: Multiline,
+
+    /// The order in which the entries of maps and sets are written.
+    pub order: EntryOrder,
+
+    /// The colors used to write the different parts of the output.
+    pub theme: Theme,
+
+    /// The policy for choosing the indentation unit of each level.
+    pub indent: IndentPolicy,
+
+    /// The policy for drawing guide rails in the indentation.
+    pub guides: Guides,
+
+    /// The maximum depth of the values that are written. Components of
+    /// values at this depth are replaced by the `elision` marker.
+    /// `None` means that there is no limit.
+    pub max_depth: Option<usize>,
+
+    /// The maximum depth of the values that can be written at all. Values
+    /// nested deeper make the stringification fail with a
+    /// `StringifyError::DepthLimitExceeded`, e.g. to guard against cycles
+    /// through `Rc`s. `None` means that there is no limit.
+    pub depth_limit: Option<usize>,
+
+    /// The maximum number of elements of a sequence, map or set that are
+    /// written. The rest is summarized by the `elision` marker followed
+    /// by the number of elements that were left out, e.g. `… (4321 more)`.
+    /// `None` means that there is no limit.
+    pub max_elements: Option<usize>,
+
+    /// The maximum number of bytes written by `Stringify2::stringify_root`,
+    /// after which the stringification stops and the `elision` marker is
+    /// written followed by `(truncated)`. `None` means that there is no limit.
+    pub max_bytes: Option<usize>,
+
+    /// The line ending written for a `Style` with `Newline::Add`.
+    pub line_ending: LineEnding,
+
+    /// The prefix written at the start of every line by
+    /// `Stringify2::stringify_root`, e.g. `// ` to embed the output in a
+    /// code comment. It doesn't count towards the width of `Layout::Fit`
+    /// or the `max_bytes`.
+    pub line_prefix: LinePrefix,
+
+    /// Whether `Stringify2::stringify_root` numbers the lines of the output
+    /// in a gutter, e.g. to refer to a line of a dump in a review. This
+    /// holds the whole output back until it is complete, to find out how
+    /// wide the gutter must be. The gutter comes before the `line_prefix`.
+    pub line_numbers: bool,
+
+    /// The marker that replaces values beyond the `max_depth`, elements
+    /// beyond the `max_elements` and output beyond the `max_bytes`.
+    pub elision: Elision,
+
+    /// The policy for writing the labels of containers e.g. `Vec`.
+    pub labels: Labels,
+
+    /// The delimiters and separators of containers and their elements.
+    pub delimiters: Delimiters,
+
+    /// Whether line breaks and indentation are written at all, or only
+    /// for values that don't fit on a line.
+    pub layout: Layout,
+
+    /// The policy for writing a separator after the last element of a
+    /// container.
+    pub trailing: TrailingSeparator,
+
+    /// The policy for writing `Redacted` values.
+    pub redaction: Redaction,
+
+    /// Whether sequences of sequences of scalars, e.g. a `Vec<Vec<f64>>`,
+    /// are written as grids, with each inner sequence on a single line and
+    /// the columns of its elements padded to equal widths.
+    pub grid: bool,
+
+    /// Whether a value shared by several `Rc`s or `Arc`s is written only
+    /// once. The other occurrences are written as a reference to the path
+    /// of the first one instead, e.g. `<same as .services[2].config>`.
+    pub dedup: bool,
+
+    /// Whether the field names of a struct and the keys of a map are padded
+    /// to the width of the widest one, so that the delimiters after them,
+    /// and with that the values, line up within the container. This has
+    /// no effect on values that are written on a single line.
+    pub align: bool,
+
+    /// Whether each element of a sequence is prefixed with its index, as
+    /// in `[42]: value`, e.g. to refer to an element of a long dump. Such
+    /// sequences are never written as grids.
+    pub indices: bool,
+
+    /// Whether the opening of a non-empty sequence, set or map includes
+    /// its number of elements, as in `HashMap (273 entries) {`, so that
+    /// the size is known even if not all elements are written.
+    pub sizes: bool,
+
+    /// Whether fields whose values are unset, e.g. `None`, an empty
+    /// collection or 0 (see `Stringify2::is_unset`), are left out, so that
+    /// only what is set shows up e.g. in a dump of a sparse config.
+    pub skip_unset: bool,
+
+    /// Whether the output is canonical, i.e. the same bytes for equal
+    /// values regardless of the run, platform or crate features, e.g. to
+    /// hash it. Floats are written by their `Display` impl even with the
+    /// `ryu` feature. Indentation at the end of lines is left out, but
+    /// whitespace that belongs to values is kept, so that e.g. `"a "` and
+    /// `"a"` are told apart. Use `Options::canonical` for
+    /// the other options that this relies on.
+    pub canonical: bool,
+
+    /// Custom renderings that replace the `Stringify2` impls of types.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub formatters: Formatters,
+}
+
+impl Options {
+    /// The `Options` for canonical output (see `Options::canonical`):
+    /// the defaults, but with the entries of maps and sets sorted, with
+    /// `\n` line endings and without colors.
+    pub fn canonical() -> Self {
+        Self {
+            order: EntryOrder::Sorted,
+            line_ending: LineEnding::Lf,
+            theme: Theme::plain(),
+            canonical: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_none(mut self, none: NoneStyle) -> Self {
+        self.none = none;
+        self
+    }
+
+    pub fn with_number(mut self, number: NumberFormat) -> Self {
+        self.number = number;
+        self
+    }
+
+    pub fn with_quoting(mut self, quoting: Quoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    pub fn with_multiline(mut self, multiline: Multiline) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    pub fn with_order(mut self, order: EntryOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_indent(mut self, indent: IndentPolicy) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn with_guides(mut self, guides: Guides) -> Self {
+        self.guides = guides;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_depth_limit(mut self, depth_limit: Option<usize>) -> Self {
+        self.depth_limit = depth_limit;
+        self
+    }
+
+    pub fn with_max_elements(mut self, max_elements: Option<usize>) -> Self {
+        self.max_elements = max_elements;
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: Option<usize>) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    pub fn with_line_prefix(mut self, line_prefix: LinePrefix) -> Self {
+        self.line_prefix = line_prefix;
+        self
+    }
+
+    pub fn with_line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    pub fn with_elision(mut self, elision: Elision) -> Self {
+        self.elision = elision;
+        self
+    }
+
+    pub fn with_labels(mut self, labels: Labels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    pub fn with_delimiters(mut self, delimiters: Delimiters) -> Self {
+        self.delimiters = delimiters;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_trailing(mut self, trailing: TrailingSeparator) -> Self {
+        self.trailing = trailing;
+        self
+    }
+
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    pub fn with_grid(mut self, grid: bool) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    pub fn with_align(mut self, align: bool) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_indices(mut self, indices: bool) -> Self {
+        self.indices = indices;
+        self
+    }
+
+    pub fn with_sizes(mut self, sizes: bool) -> Self {
+        self.sizes = sizes;
+        self
+    }
+
+    pub fn with_skip_unset(mut self, skip_unset: bool) -> Self {
+        self.skip_unset = skip_unset;
+        self
+    }
+
+    pub fn with_canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Render values of type `T` with `format` (see `Formatters::register`).
+    pub fn with_formatter<T, F>(mut self, format: F) -> Self
+    where T: 'static,
+          F: Fn(&T) -> String + Send + Sync + 'static {
+        self.formatters.register(format);
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NoneStyle {
+    /// Render `None` as `None`.
+    #[default]
+    Print,
+    /// Render `None` as nothing at all.
+    Omit,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Redaction {
+    /// Render `Redacted` values as `***`.
+    #[default]
+    Mask,
+    /// Render `Redacted` values as a hash of their stringification e.g.
+    /// `#4f0c8e21d5a7b3f9`, so that equal secrets can be recognized as
+    /// such without being revealed. The hash is stable within a build of
+    /// the crate, but not across Rust releases.
+    Hash,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quoting {
+    /// Write strings and chars verbatim.
+    #[default]
+    Raw,
+    /// Wrap strings in `"` and chars in `'`, and escape quotes, backslashes
+    /// and non-printable characters such as `\n` and `\t`.
+    Quoted,
+}
+
+/// Only applies to strings written with `Quoting::Raw`, since quoted
+/// strings have their newlines escaped.
+/// The lines of a block are indented with the `"end"` style, at the depth
+/// of the string i.e. at the level of the other elements of its container.
+/// Strings that are written on a single line, e.g. with `Layout::Compact`,
+/// have their lines separated by a space instead.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Multiline {
+    /// Write the string verbatim.
+    #[default]
+    Verbatim,
+    /// Prefix each continuation line with the current indentation:
+    /// ```text
+    /// first line
+    ///     second line
+    /// ```
+    Indented,
+    /// Start the string on a new line, and prefix each line with the
+    /// current indentation followed by a `|` margin marker. Elements,
+    /// which start on a line of their own, start on that line:
+    /// ```text
+    /// Vec [
+    ///     | first line
+    ///     | second line,
+    /// ]
+    /// ```
+    Margin,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntryOrder {
+    /// Write entries in iteration order. For collections without an
+    /// inherent order such as `HashMap`, this may differ between runs.
+    #[default]
+    Iteration,
+    /// Write the entries of collections without an inherent order such as
+    /// `HashMap` and `HashSet` sorted by the stringification of their keys,
+    /// and of their values where keys stringify alike, which makes the
+    /// output deterministic. Ordered collections are written in their own
+    /// order.
+    Sorted,
+    /// Write the entries of all maps and sets in the order defined by
+    /// the comparator.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(EntryComparator),
+}
+
+/// The stringified key and value of a map entry, as seen by an
+/// `EntryComparator`. For the elements of a set, `value` is empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Entry<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+type CompareFn = dyn Fn(&Entry, &Entry) -> cmp::Ordering + Send + Sync;
+
+/// A domain-specific order for the entries of maps and sets.
+/// Since entries can be of any type, they are compared by their stringification.
+#[derive(Clone)]
+pub struct EntryComparator(Arc<CompareFn>);
+
+impl EntryComparator {
+    /// Order entries using the comparator `compare`.
+    pub fn new<F>(compare: F) -> Self
+    where F: Fn(&Entry, &Entry) -> cmp::Ordering + Send + Sync + 'static {
+        EntryComparator(Arc::new(compare))
+    }
+
+    /// Order entries by the key extracted from each entry by `extract`,
+    /// e.g. `EntryComparator::by(|entry| entry.key.len())`.
+    pub fn by<F, K>(extract: F) -> Self
+    where F: Fn(&Entry) -> K + Send + Sync + 'static,
+          K: Ord {
+        Self::new(move |a, b| extract(a).cmp(&extract(b)))
+    }
+
+    pub fn compare(&self, a: &Entry, b: &Entry) -> cmp::Ordering {
+        (self.0)(a, b)
+    }
+}
+
+impl fmt::Debug for EntryComparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("EntryComparator")
+    }
+}
+
+/// Two comparators are equal if they share the same comparison function.
+impl PartialEq for EntryComparator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for EntryComparator {}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NumberFormat {
+    /// The number of digits written after the decimal point of a float.
+    /// `None` means as many as needed to represent the value exactly.
+    pub precision: Option<usize>,
+
+    /// The notation used for floats.
+    pub notation: Notation,
+
+    /// The radix used for integers.
+    pub radix: Radix,
+
+    /// The digit grouping policy for integers.
+    pub grouping: Grouping,
+}
+
+impl NumberFormat {
+    pub fn with_precision(&self, precision: Option<usize>) -> Self {
+        Self { precision, ..*self }
+    }
+
+    pub fn with_notation(&self, notation: Notation) -> Self {
+        Self { notation, ..*self }
+    }
+
+    pub fn with_radix(&self, radix: Radix) -> Self {
+        Self { radix, ..*self }
+    }
+
+    pub fn with_grouping(&self, grouping: Grouping) -> Self {
+        Self { grouping, ..*self }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Notation {
+    /// e.g. `1234.5`
+    #[default]
+    Fixed,
+    /// e.g. `1.2345e3`
+    Scientific,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Radix {
+    /// e.g. `42`
+    #[default]
+    Decimal,
+    /// e.g. `0x2a`
+    Hexadecimal,
+    /// e.g. `0o52`
+    Octal,
+    /// e.g. `0b101010`
+    Binary,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Grouping {
+    /// e.g. `1234567`
+    #[default]
+    Off,
+    /// Separate groups of digits with the given character, e.g. `1_234_567`
+    /// or `1,234,567`. Hexadecimal and binary digits are grouped by 4,
+    /// decimal and octal digits by 3.
+    Separator(char),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndentPolicy {
+    /// Use `Style::indent` as the unit of every level.
+    #[default]
+    Uniform,
+    /// Use the unit at index `n` for level `n + 1`, e.g. 2 spaces for the
+    /// first level and 4 spaces for deeper ones. Levels deeper than the
+    /// number of units reuse the last unit. Without any units, this is
+    /// the same as `IndentPolicy::Uniform`.
+    PerLevel(Vec<Cow<'static, str>>),
+    /// Compute the unit of each level (starting at 1) with a closure.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(IndentFn),
+}
+
+impl IndentPolicy {
+    /// The unit of indentation written for `level` (starting at 1),
+    /// where `style` provides the indentation unit for `IndentPolicy::Uniform`.
+    pub fn unit<'s>(&'s self, style: &'s Style, level: usize) -> Cow<'s, str> {
+        match self {
+            IndentPolicy::Uniform => Cow::Borrowed(&style.indent),
+            IndentPolicy::PerLevel(units) => match units.get(level - 1).or(units.last()) {
+                Some(unit) => Cow::Borrowed(unit),
+                None => Cow::Borrowed(&style.indent),
+            },
+            IndentPolicy::Custom(unit) => unit.unit(level),
+        }
+    }
+}
+
+/// Guide rails make it easy to see which lines belong to the same level:
+/// ```text
+/// Vec [
+/// │   Vec [
+/// │   │   1,
+/// │   ],
+/// ]
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Guides {
+    /// Write indentation units as they are.
+    #[default]
+    Off,
+    /// Replace the first character of the indentation unit of every
+    /// level with the given character, e.g. `'│'`.
+    Rail(char),
+}
+
+impl Guides {
+    pub const RAIL: char = '│';
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`, e.g. for files that are read on Windows.
+    CrLf,
+    /// `\r\n` on Windows, and `\n` everywhere else.
+    Native,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Native if cfg!(windows) => "\r\n",
+            LineEnding::Native => "\n",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinePrefix {
+    /// Write lines as they are.
+    #[default]
+    None,
+    /// Write the given text before every line, e.g. `> ` for a quote.
+    Text(Cow<'static, str>),
+    /// Compute the prefix of each line with a closure, from its number
+    /// (starting at 1) and its indentation level.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(PrefixFn),
+}
+
+impl LinePrefix {
+    pub fn is_none(&self) -> bool {
+        *self == LinePrefix::None
+    }
+
+    /// The prefix of the line with the number `line` (starting at 1),
+    /// which is indented by `level` levels.
+    pub fn text(&self, line: usize, level: usize) -> Cow<'_, str> {
+        match self {
+            LinePrefix::None => Cow::Borrowed(""),
+            LinePrefix::Text(text) => Cow::Borrowed(text),
+            LinePrefix::Custom(prefix) => prefix.prefix(line, level),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Elision {
+    /// `…`
+    #[default]
+    Ellipsis,
+    /// A custom marker, e.g. `<elided>`.
+    Text(Cow<'static, str>),
+}
+
+impl Elision {
+    pub fn text(&self) -> &str {
+        match self {
+            Elision::Ellipsis => "…",
+            Elision::Text(text) => text,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Labels {
+    /// Write containers with their labels e.g. `Vec [` and `HashMap {`.
+    #[default]
+    Print,
+    /// Write containers without labels e.g. `[` and `{`.
+    Omit,
+    /// Replace the labels that are keys of the map by their values,
+    /// e.g. `"Vec" => "list"`. An empty value omits the label.
+    /// Other labels are written as they are.
+    Replace(BTreeMap<Cow<'static, str>, Cow<'static, str>>),
+}
+
+impl Labels {
+    /// Return the text to write in place of the container `label`.
+    pub fn label<'l>(&'l self, label: &'l str) -> &'l str {
+        match self {
+            Labels::Print => label,
+            Labels::Omit => "",
+            Labels::Replace(labels) => labels.get(label).map_or(label, |text| text),
+        }
+    }
+
+    /// Return the opening of a container e.g. `Vec [`, or `[` if its label
+    /// is omitted, where `delimiter` is the opening delimiter.
+    pub fn open(&self, label: &str, delimiter: &str) -> String {
+        match self.label(label) {
+            "" => delimiter.to_string(),
+            label => format!("{} {}", label, delimiter),
+        }
+    }
+}
+
+/// The punctuation written around and between the elements of containers.
+/// Delimiter pairs consist of an opening and a closing delimiter.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Delimiters {
+    /// The delimiters of sequences e.g. `Vec` and arrays.
+    pub seq: (Cow<'static, str>, Cow<'static, str>),
+    /// The delimiters of maps.
+    pub map: (Cow<'static, str>, Cow<'static, str>),
+    /// The delimiters of sets.
+    pub set: (Cow<'static, str>, Cow<'static, str>),
+    /// The delimiters of tuples.
+    pub tuple: (Cow<'static, str>, Cow<'static, str>),
+    /// The separator between the key and the value of a map entry.
+    pub key_value: Cow<'static, str>,
+    /// The separator between the name and the value of a field.
+    pub field_value: Cow<'static, str>,
+    /// The separator written after each element and field.
+    pub separator: Cow<'static, str>,
+}
+
+impl Delimiters {
+    /// Rust-like syntax e.g. `Vec [1, 2]`, `HashMap {k : v}` and `name=value`.
+    pub const RUST: Self = Self {
+        seq: (Cow::Borrowed("["), Cow::Borrowed("]")),
+        map: (Cow::Borrowed("{"), Cow::Borrowed("}")),
+        set: (Cow::Borrowed("{"), Cow::Borrowed("}")),
+        tuple: (Cow::Borrowed("("), Cow::Borrowed(")")),
+        key_value: Cow::Borrowed(" : "),
+        field_value: Cow::Borrowed("="),
+        separator: Cow::Borrowed(","),
+    };
+
+    /// JSON-like syntax e.g. `[1, 2]`, `{k: v}` and `name: value`.
+    /// Combine it with `Labels::Omit` to leave out the container labels.
+    pub const JSON: Self = Self {
+        seq: (Cow::Borrowed("["), Cow::Borrowed("]")),
+        map: (Cow::Borrowed("{"), Cow::Borrowed("}")),
+        set: (Cow::Borrowed("["), Cow::Borrowed("]")),
+        tuple: (Cow::Borrowed("["), Cow::Borrowed("]")),
+        key_value: Cow::Borrowed(": "),
+        field_value: Cow::Borrowed(": "),
+        separator: Cow::Borrowed(","),
+    };
+}
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Self::RUST
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layout {
+    /// Write line breaks and indentation as the `Style`s ask for.
+    #[default]
+    Expanded,
+    /// Write everything on a single line regardless of the `Style`s,
+    /// with a space after each separator and no trailing separators,
+    /// e.g. `Vec [1, 2, 3]` and `HashMap {a : 1, b : 2}`.
+    Compact,
+    /// Write each value like `Compact` if it fits within `width` columns
+    /// on the current line, and like `Expanded` otherwise.
+    Fit { width: usize },
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrailingSeparator {
+    /// Write a separator after the last element e.g. `[1, 2,]`,
+    /// unless the `Layout` is `Compact`.
+    #[default]
+    Always,
+    /// Only write separators between elements e.g. `[1, 2]`.
+    Never,
+    /// Write a separator after the last element only if the closing
+    /// delimiter is written on a line of its own.
+    OnlyMultiline,
+}
+
+type UnitFn = dyn Fn(usize) -> Cow<'static, str> + Send + Sync;
+
+/// A closure that computes the indentation unit for a given level.
+#[derive(Clone)]
+pub struct IndentFn(Arc<UnitFn>);
+
+impl IndentFn {
+    pub fn new<F>(unit: F) -> Self
+    where F: Fn(usize) -> Cow<'static, str> + Send + Sync + 'static {
+        IndentFn(Arc::new(unit))
+    }
+
+    pub fn unit(&self, level: usize) -> Cow<'static, str> {
+        (self.0)(level)
+    }
+}
+
+impl fmt::Debug for IndentFn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("IndentFn")
+    }
+}
+
+/// Two `IndentFn`s are equal if they share the same closure.
+impl PartialEq for IndentFn {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for IndentFn {}
+
+type LineFn = dyn Fn(usize, usize) -> Cow<'static, str> + Send + Sync;
+
+/// A closure that computes the prefix of a line from its number and its
+/// indentation level.
+#[derive(Clone)]
+pub struct PrefixFn(Arc<LineFn>);
+
+impl PrefixFn {
+    pub fn new<F>(prefix: F) -> Self
+    where F: Fn(usize, usize) -> Cow<'static, str> + Send + Sync + 'static {
+        PrefixFn(Arc::new(prefix))
+    }
+
+    pub fn prefix(&self, line: usize, level: usize) -> Cow<'static, str> {
+        (self.0)(line, level)
+    }
+}
+
+impl fmt::Debug for PrefixFn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PrefixFn")
+    }
+}
+
+/// Two `PrefixFn`s are equal if they share the same closure.
+impl PartialEq for PrefixFn {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for PrefixFn {}
+
+type FormatFn = dyn Fn(&dyn Any) -> String + Send + Sync;
+
+/// A registry of closures that render values of a given type, which take
+/// precedence over the `Stringify2` impl of that type, e.g. to render all
+/// `Duration`s as `1.5s` or to redact all `Password`s.
+/// They are consulted for values whose `Stringify2::as_any` returns `Some`,
+/// which is the case for the primitive types and for derived impls of
+/// types without generic parameters.
+#[derive(Clone, Default)]
+pub struct Formatters(BTreeMap<TypeId, Arc<FormatFn>>);
+
+impl Formatters {
+    /// Render values of type `T` with `format`, replacing any closure
+    /// that was registered for `T` before.
+    pub fn register<T, F>(&mut self, format: F)
+    where T: 'static,
+          F: Fn(&T) -> String + Send + Sync + 'static {
+        let format = move |value: &dyn Any| match value.downcast_ref::<T>() {
+            Some(value) => format(value),
+            None => String::new(),
+        };
+        self.0.insert(TypeId::of::<T>(), Arc::new(format));
+    }
+
+    /// Render `value` with the closure registered for its type, if any.
+    pub fn format(&self, value: &dyn Any) -> Option<String> {
+        self.0.get(&value.type_id()).map(|format| format(value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Formatters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Formatters({})", self.0.len())
+    }
+}
+
+/// Two registries are equal if they share the same closures for the same types.
+impl PartialEq for Formatters {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().zip(&other.0).all(|((a, f), (b, g))| a == b && Arc::ptr_eq(f, g))
+    }
+}
+
+impl Eq for Formatters {}
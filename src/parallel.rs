@@ -0,0 +1,124 @@
+use crate::context::{PathSegment, StringifyContext};
+use crate::error::StringifyResult;
+use crate::layout::LineStartWriter;
+use crate::node::Node;
+use crate::options::Layout;
+use crate::stringify::{
+    open_sized, seq_hint, seq_node, stringify_seq, write_index, write_remainder, Stringify2, ELEMENTS
+};
+use crate::styles::{Style, StyleKey, Styles};
+use crate::theme::Role;
+use rayon::prelude::*;
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// A wrapper that stringifies the elements of a large slice in parallel,
+/// e.g. to dump a dataset with millions of rows. The elements are split
+/// into chunks of `CHUNK_LEN` that are each stringified into a buffer of
+/// their own on the `rayon` thread pool, after which the buffers are
+/// written in order. The output is the same as that of a `Vec`:
+/// ```text
+/// stringifier.write(&Parallel(&rows), &mut file)?;
+/// ```
+/// Slices with fewer than `THRESHOLD` elements are written on the current
+/// thread, and so are slices written with `Layout::Fit` or on a single
+/// line, as a grid (see `Options::grid`) or with `Options::dedup`.
+#[derive(Copy, Clone)]
+pub struct Parallel<'a, T>(pub &'a [T]);
+
+impl<T> Parallel<'_, T> {
+    pub const THRESHOLD: usize = 4096;
+    pub const CHUNK_LEN: usize = 1024;
+}
+
+impl<T> Stringify2 for Parallel<'_, T>
+where T: Stringify2 + Sync {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let options = styles.options();
+        let count = self.0.len().min(options.max_elements.unwrap_or(usize::MAX));
+        let serial = count < Self::THRESHOLD || options.layout != Layout::Expanded || ctx.is_flat();
+        if serial || options.grid || options.dedup {
+            return stringify_seq(self, buf, styles, ctx, "Vec", self.0.iter());
+        }
+        let end: Style = styles.get_or_default(StyleKey::End);
+        let (open, close) = &options.delimiters.seq;
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+        let opening = open_sized(styles, "Vec", open, self.0.len(), ELEMENTS);
+        options.theme.write(buf, Role::Container, &opening)?;
+        let (depth, path) = (ctx.depth(), ctx.path().to_vec());
+        let chunks = self.0[.. count].par_chunks(Self::CHUNK_LEN).enumerate().map(|(chunk_idx, chunk)| {
+            let mut ctx = StringifyContext::at(depth, path.clone());
+            let mut chunk_buf = Chunk::new(ctx.line_level_cell());
+            let mut writer = LineStartWriter::new(&mut chunk_buf, ctx.indenting_cell(), ctx.line_started_cell());
+            for (offset, item) in chunk.iter().enumerate() {
+                let idx = chunk_idx * Self::CHUNK_LEN + offset;
+                ctx.nested(PathSegment::Index(idx), |ctx| {
+                    self.indent(&mut writer, styles, ctx, &end)?;
+                    write_index(&mut writer, styles, idx)?;
+                    item.stringify_component(&mut writer, styles, ctx)
+                })?;
+                self.write_separator(&mut writer, styles, &ctx, idx + 1 == self.0.len())?;
+            }
+            Ok((chunk_buf.bytes, chunk_buf.lines))
+        }).collect::<StringifyResult<Vec<_>>>()?;
+        let level = ctx.line_level_cell();
+        for (bytes, lines) in chunks {
+            let mut start = 0;
+            for (line_start, line_level) in lines {
+                buf.write_all(&bytes[start .. line_start])?;
+                level.set(line_level);
+                start = line_start;
+            }
+            buf.write_all(&bytes[start ..])?;
+        }
+        write_remainder(self, buf, styles, ctx, &end, self.0.len() - count)?;
+        self.indent(buf, styles, ctx, &end)?;
+        options.theme.write(buf, Role::Container, close)?;
+        Ok(())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("Vec", styles, self.0.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("Vec", self.0.iter())
+    }
+}
+
+/// The output of a chunk of elements, along with the indentation level of
+/// each of its lines after the first one, by the offset at which the line
+/// starts, so that they can be passed on to the `LinePrefix` of the root
+/// value when the chunk is written.
+struct Chunk {
+    bytes: Vec<u8>,
+    level: Rc<Cell<usize>>,
+    lines: Vec<(usize, usize)>,
+}
+
+impl Chunk {
+    fn new(level: Rc<Cell<usize>>) -> Self {
+        Self { bytes: vec![], level, lines: vec![] }
+    }
+}
+
+impl Write for Chunk {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for (idx, &byte) in bytes.iter().enumerate() {
+            if byte == b'\n' {
+                self.lines.push((self.bytes.len() + idx + 1, self.level.get()));
+            }
+        }
+        self.bytes.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
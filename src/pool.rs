@@ -0,0 +1,63 @@
+use std::sync::Mutex;
+
+/// A pool of `String`s to stringify values into, so that a hot path e.g.
+/// of logging can reuse the buffers of earlier stringifications rather
+/// than allocating a new one every time:
+/// ```text
+/// let buf = stringifier.take_buffer();
+/// let text = stringifier.to_string_in(&request, buf)?;
+/// log::info!("{}", text);
+/// stringifier.return_buffer(text);
+/// ```
+/// The pool keeps at most `max_buffers` buffers, and drops returned
+/// buffers with a capacity over `max_capacity` so that a single huge
+/// value doesn't keep its memory alive.
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<String>>,
+    max_buffers: usize,
+    max_capacity: usize,
+}
+
+impl BufferPool {
+    pub const MAX_BUFFERS: usize = 16;
+    pub const MAX_CAPACITY: usize = 1 << 20; // 1 MiB
+
+    pub fn new(max_buffers: usize, max_capacity: usize) -> Self {
+        Self { buffers: Mutex::new(vec![]), max_buffers, max_capacity }
+    }
+
+    /// Take an empty buffer from the pool, or a new one if it is empty.
+    pub fn take(&self) -> String {
+        let mut buffers = self.buffers.lock().unwrap_or_else(|err| err.into_inner());
+        buffers.pop().unwrap_or_default()
+    }
+
+    /// Return `buf` to the pool, which clears it.
+    pub fn give(&self, mut buf: String) {
+        if buf.capacity() == 0 || buf.capacity() > self.max_capacity {
+            return;
+        }
+        buf.clear();
+        let mut buffers = self.buffers.lock().unwrap_or_else(|err| err.into_inner());
+        if buffers.len() < self.max_buffers {
+            buffers.push(buf);
+        }
+    }
+
+    /// The number of buffers that are available in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.lock().unwrap_or_else(|err| err.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A pool of at most `MAX_BUFFERS` buffers of up to `MAX_CAPACITY` bytes.
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(Self::MAX_BUFFERS, Self::MAX_CAPACITY)
+    }
+}
@@ -0,0 +1,55 @@
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::options::Redaction;
+use crate::stringify::Stringify2;
+use crate::styles::Styles;
+use crate::theme::Role;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io::{self, Write};
+
+/// A wrapper that hides the value it wraps, so that values containing
+/// secrets such as passwords and tokens can be safely written to logs.
+/// Depending on the `Redaction` in the `Options`, it is written as `***`
+/// or as a hash of the stringification of the wrapped value.
+/// Fields can also be redacted with `#[stringify(redact)]` when deriving.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Redacted<T: ?Sized>(pub T);
+
+impl<T> Stringify2 for Redacted<T>
+where T: Stringify2 + ?Sized {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    _: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let theme = styles.options().theme;
+        match styles.options().redaction {
+            Redaction::Mask => theme.write(buf, Role::Container, "***")?,
+            Redaction::Hash => {
+                // The hash must not depend on the styles of the surrounding
+                // stringification, so the value is stringified without them.
+                let mut hasher = HashWriter(DefaultHasher::new());
+                self.0.stringify(&mut hasher, &Styles::default(), &mut StringifyContext::new())?;
+                let hash = format!("#{:016x}", hasher.0.finish());
+                theme.write(buf, Role::Container, &hash)?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// A writer that feeds the bytes written to it into a `Hasher`.
+struct HashWriter<H>(H);
+
+impl<H> Write for HashWriter<H>
+where H: Hasher {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.write(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
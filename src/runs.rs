@@ -0,0 +1,93 @@
+use crate::context::{PathSegment, StringifyContext};
+use crate::error::StringifyResult;
+use crate::node::Node;
+use crate::stringify::{
+    open_sized, seq_hint, seq_node, stringify_seq, write_index, write_remainder, Stringify2, ELEMENTS
+};
+use crate::styles::{Style, StyleKey, Styles};
+use crate::theme::Role;
+use std::io::Write;
+
+/// A wrapper that writes a slice like a `Vec`, except that each run of at
+/// least `MIN_LEN` consecutive equal elements is collapsed into its first
+/// element followed by the length of the run, like debuggers do:
+/// ```text
+/// Vec [
+///     1,
+///     0 × 4093,
+///     2,
+/// ]
+/// ```
+/// The index of a run is that of its first element, and `max_elements`
+/// limits the number of runs that are written. The `Node` of the slice
+/// has all elements, so the runs are only collapsed by `stringify`.
+#[derive(Copy, Clone)]
+pub struct Runs<'a, T>(pub &'a [T]);
+
+impl<T> Runs<'_, T>
+where T: PartialEq {
+    pub const MIN_LEN: usize = 4;
+
+    /// The index and length of each run, where the elements of runs that
+    /// are shorter than `MIN_LEN` are runs of a single element.
+    fn runs(&self) -> Vec<(usize, usize)> {
+        let mut runs = vec![];
+        let mut start = 0;
+        while start < self.0.len() {
+            let len = self.0[start ..].iter().take_while(|item| **item == self.0[start]).count();
+            match len < Self::MIN_LEN {
+                true => runs.extend((start .. start + len).map(|idx| (idx, 1))),
+                false => runs.push((start, len)),
+            }
+            start += len;
+        }
+        runs
+    }
+}
+
+impl<T> Stringify2 for Runs<'_, T>
+where T: Stringify2 + PartialEq {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let runs = self.runs();
+        if runs.len() == self.0.len() {
+            return stringify_seq(self, buf, styles, ctx, "Vec", self.0.iter());
+        }
+        let options = styles.options();
+        let end: Style = styles.get_or_default(StyleKey::End);
+        let (open, close) = &options.delimiters.seq;
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+        let opening = open_sized(styles, "Vec", open, self.0.len(), ELEMENTS);
+        options.theme.write(buf, Role::Container, &opening)?;
+        let max_elements = options.max_elements.unwrap_or(usize::MAX);
+        let mut written = 0;
+        for (n, &(idx, len)) in runs.iter().take(max_elements).enumerate() {
+            ctx.nested(PathSegment::Index(idx), |ctx| -> StringifyResult<()> {
+                self.indent(buf, styles, ctx, &end)?;
+                write_index(buf, styles, idx)?;
+                self.0[idx].stringify_component(buf, styles, ctx)?;
+                if len > 1 {
+                    options.theme.write(buf, Role::Container, &format!(" × {}", len))?;
+                }
+                Ok(())
+            })?;
+            self.write_separator(buf, styles, ctx, n + 1 == runs.len())?;
+            written = idx + len;
+        }
+        write_remainder(self, buf, styles, ctx, &end, self.0.len() - written)?;
+        self.indent(buf, styles, ctx, &end)?;
+        options.theme.write(buf, Role::Container, close)?;
+        Ok(())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("Vec", styles, self.0.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("Vec", self.runs().into_iter().map(|(idx, _)| &self.0[idx]))
+    }
+}
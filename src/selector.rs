@@ -0,0 +1,88 @@
+use crate::context::PathSegment;
+use crate::error::{StringifyError, StringifyResult};
+use std::fmt;
+use std::str::FromStr;
+
+/// A pattern that matches the paths of values in a stringification,
+/// e.g. `servers[*].port`. Paths are relative to the root value, so
+/// the leading `.` of a field can be omitted. A selector consists of:
+/// - `.name` for the field `name`, or `.*` for any field
+/// - `[3]` for the element at index 3, or `[key]` for the value at `key`
+/// - `[*]` for any element or value
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Selector {
+    segments: Vec<SelectorSegment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum SelectorSegment {
+    Field(String),
+    AnyField,
+    Index(usize),
+    Key(String),
+    AnyIndex,
+}
+
+impl Selector {
+    /// Return `true` if `path` is matched by `self` as a whole.
+    pub fn matches(&self, path: &[PathSegment]) -> bool {
+        self.segments.len() == path.len()
+            && self.segments.iter().zip(path).all(|pair| match pair {
+                (SelectorSegment::Field(name), PathSegment::Field(field)) => name == field,
+                (SelectorSegment::AnyField, PathSegment::Field(_)) => true,
+                (SelectorSegment::Index(idx), PathSegment::Index(index)) => idx == index,
+                (SelectorSegment::Key(key), PathSegment::Key(k)) => key == k,
+                (SelectorSegment::AnyIndex, PathSegment::Index(_)) => true,
+                (SelectorSegment::AnyIndex, PathSegment::Key(_)) => true,
+                _ => false,
+            })
+    }
+}
+
+impl FromStr for Selector {
+    type Err = StringifyError;
+
+    fn from_str(selector: &str) -> StringifyResult<Self> {
+        let invalid = || StringifyError::InvalidSelector { selector: selector.to_string() };
+        let mut segments = vec![];
+        let mut rest = selector;
+        while !rest.is_empty() {
+            if let Some(bracketed) = rest.strip_prefix('[') {
+                let close = bracketed.find(']').ok_or_else(invalid)?;
+                let inner = &bracketed[.. close];
+                segments.push(match inner.parse() {
+                    _ if inner == "*" => SelectorSegment::AnyIndex,
+                    Ok(idx) => SelectorSegment::Index(idx),
+                    Err(_) => SelectorSegment::Key(inner.to_string()),
+                });
+                rest = &bracketed[close + 1 ..];
+            } else {
+                let field = rest.strip_prefix('.').unwrap_or(rest);
+                let end = field.find(['.', '[']).unwrap_or(field.len());
+                let name = &field[.. end];
+                segments.push(match name {
+                    "" => return Err(invalid()),
+                    "*" => SelectorSegment::AnyField,
+                    name => SelectorSegment::Field(name.to_string()),
+                });
+                rest = &field[end ..];
+            }
+        }
+        Ok(Selector { segments })
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                SelectorSegment::Field(name) => write!(f, ".{}", name)?,
+                SelectorSegment::AnyField => write!(f, ".*")?,
+                SelectorSegment::Index(idx) => write!(f, "[{}]", idx)?,
+                SelectorSegment::Key(key) => write!(f, "[{}]", key)?,
+                SelectorSegment::AnyIndex => write!(f, "[*]")?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+use crate::batched::Batched;
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::newline::Newline;
+use crate::node::{Json, Node, Render, Ron};
+use crate::options::{Labels, Options};
+use crate::pool::BufferPool;
+use crate::stringify::{stringify_root_in, Stringify2};
+use crate::table::{Csv, Table};
+use crate::styles::{Style, StyleKey, Styles};
+use crate::theme::Theme;
+use std::cell::Cell;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A facade that owns the `Styles` and `Options` used to stringify values,
+/// so that they don't have to be passed to every call site by hand:
+/// ```text
+/// let stringifier = Stringifier::default().with_theme(Theme::dark().auto());
+/// stringifier.print(&config)?;
+/// ```
+pub struct Stringifier {
+    styles: Styles,
+    flush: Flush,
+    pool: Arc<BufferPool>,
+}
+
+/// When `Stringifier::stream` flushes the sink that it writes a value to.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Flush {
+    /// Flush after the opening line and after each member of the root
+    /// value, so that a reader gets every member as soon as it is written.
+    PerItem,
+    /// Flush whenever at least this many bytes have been written since
+    /// the last time.
+    Bytes(usize),
+    /// Only flush once the whole value has been written.
+    #[default]
+    Never,
+}
+
+impl Stringifier {
+    pub fn new(styles: Styles) -> Self {
+        Self { styles, flush: Flush::default(), pool: Arc::default() }
+    }
+
+    /// A `Stringifier` like the default one, but with canonical output
+    /// (see `Options::canonical`), e.g. to hash values for change detection.
+    pub fn canonical() -> Self {
+        Self::default().with_options(Options::canonical())
+    }
+
+    pub fn styles(&self) -> &Styles {
+        &self.styles
+    }
+
+    pub fn options(&self) -> &Options {
+        self.styles.options()
+    }
+
+    pub fn flush(&self) -> Flush {
+        self.flush
+    }
+
+    pub fn pool(&self) -> &Arc<BufferPool> {
+        &self.pool
+    }
+
+    /// Replace the `Options` used by `self`.
+    pub fn with_options(self, options: Options) -> Self {
+        Self { styles: self.styles.with_options(options), ..self }
+    }
+
+    /// Replace the `Flush` strategy used by `self.stream()`.
+    pub fn with_flush(self, flush: Flush) -> Self {
+        Self { flush, ..self }
+    }
+
+    /// Replace the `BufferPool` of `self`, e.g. to share one between
+    /// several `Stringifier`s.
+    pub fn with_pool(self, pool: Arc<BufferPool>) -> Self {
+        Self { pool, ..self }
+    }
+
+    /// Replace the `Theme` in the `Options` used by `self`.
+    pub fn with_theme(self, theme: Theme) -> Self {
+        let options = self.options().clone().with_theme(theme);
+        self.with_options(options)
+    }
+
+    /// Replace the `Labels` policy in the `Options` used by `self`.
+    pub fn with_labels(self, labels: Labels) -> Self {
+        let options = self.options().clone().with_labels(labels);
+        self.with_options(options)
+    }
+
+    /// Register a formatter for values of type `T` in the `Options`
+    /// used by `self`. See `Options::with_formatter`.
+    pub fn with_formatter<T, F>(self, format: F) -> Self
+    where T: 'static,
+          F: Fn(&T) -> String + Send + Sync + 'static {
+        let options = self.options().clone().with_formatter(format);
+        self.with_options(options)
+    }
+
+    pub fn to_string<T>(&self, value: &T) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        value.stringify_new(&self.styles)
+    }
+
+    /// Stringify `value` into `buf`, which is cleared first, e.g. a buffer
+    /// from `self.take_buffer()`.
+    pub fn to_string_in<T>(&self, value: &T, buf: String) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        value.stringify_new_in(buf, &self.styles)
+    }
+
+    /// Take a buffer from the `BufferPool` of `self`.
+    pub fn take_buffer(&self) -> String {
+        self.pool.take()
+    }
+
+    /// Return a buffer to the `BufferPool` of `self` for reuse.
+    pub fn return_buffer(&self, buf: String) {
+        self.pool.give(buf)
+    }
+
+    /// Write `value` to `buf`. Small writes are coalesced into larger ones,
+    /// so that e.g. an unbuffered file isn't written to once per delimiter.
+    pub fn write<T, W>(&self, value: &T, buf: &mut W) -> StringifyResult<()>
+    where T: Stringify2 + ?Sized,
+          W: Write {
+        let mut batched = Batched::new(buf);
+        let result = value.stringify_root(&mut batched, &self.styles);
+        let finished = batched.finish();
+        result?;
+        finished?;
+        Ok(())
+    }
+
+    /// Describe `value` as a `Node`, and write that with `renderer`.
+    pub fn render<T>(&self, value: &T, renderer: &dyn Render) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        renderer.render_to_string(&Node::of(value, &self.styles)?)
+    }
+
+    /// Write `value` as JSON on a single line (see `Json`).
+    pub fn to_json<T>(&self, value: &T) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        self.render(value, &Json::compact())
+    }
+
+    /// Write `value` as JSON with one member or element per line.
+    pub fn to_json_pretty<T>(&self, value: &T) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        self.render(value, &Json::pretty())
+    }
+
+    /// Write `value` as RON with one member or element per line (see `Ron`).
+    pub fn to_ron<T>(&self, value: &T) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        self.render(value, &Ron::pretty())
+    }
+
+    /// Write the records in `value` as CSV (see `Csv`).
+    pub fn to_csv<T>(&self, value: &T) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        self.render(value, &Csv::default())
+    }
+
+    /// Write the records in `value` as a plain text table (see `Table`).
+    pub fn to_table<T>(&self, value: &T) -> StringifyResult<String>
+    where T: Stringify2 + ?Sized {
+        self.render(value, &Table::default())
+    }
+
+    /// Write `value` to `sink` as it is stringified, with the same output
+    /// as `self.write()`, and flush it as the `Flush` strategy of `self`
+    /// asks for, e.g. to stream a large value over a socket or an HTTP
+    /// response body:
+    /// ```text
+    /// let stringifier = Stringifier::default().with_flush(Flush::PerItem);
+    /// stringifier.stream(&dump, &mut response)?;
+    /// ```
+    /// Writes are buffered with a `BufWriter`, so `sink` needn't be
+    /// buffered itself. With `Flush::Bytes`, the buffer holds that many
+    /// bytes. No more of the output than that is held in memory, and the
+    /// stringification waits for as long as writing to `sink` blocks, so a
+    /// slow receiver holds it back. Only the output of
+    /// `Options::line_numbers` is held back until it is complete, as the
+    /// width of its gutter depends on the number of lines.
+    pub fn stream<T, W>(&self, value: &T, sink: W) -> StringifyResult<()>
+    where T: Stringify2 + ?Sized,
+          W: Write {
+        let sink = match self.flush {
+            Flush::Bytes(bytes) => BufWriter::with_capacity(bytes, sink),
+            _ => BufWriter::new(sink),
+        };
+        let mut ctx = StringifyContext::new();
+        let mut writer = Flushing {
+            inner: sink,
+            flush: self.flush,
+            level: ctx.line_level_cell(),
+            line: 0,
+            unflushed: 0,
+        };
+        stringify_root_in(value, &mut writer, &self.styles, &mut ctx)?;
+        writer.inner.flush()?;
+        Ok(())
+    }
+
+    /// Write `value` to stdout, followed by a line ending.
+    pub fn print<T>(&self, value: &T) -> StringifyResult<()>
+    where T: Stringify2 + ?Sized {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        self.write(value, &mut stdout)?;
+        stdout.write_all(self.options().line_ending.as_str().as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+/// A `Stringifier` that writes containers over multiple lines, with
+/// struct fields and container elements indented one level deeper
+/// than their container.
+impl Default for Stringifier {
+    fn default() -> Self {
+        Self::new(styles! {
+            StyleKey::Start => Style::standard(Newline::Omit, 0),
+            StyleKey::Name => Style::standard(Newline::Add, 1),
+            StyleKey::End => Style::standard(Newline::Add, 0)
+        })
+    }
+}
+
+/// A writer that forwards to `inner`, and flushes it as `flush` asks for.
+/// For `Flush::PerItem` it follows the level of the line that is being
+/// written, which it shares with a `StringifyContext`: a line ending between
+/// two lines at level 1 or 0 ends the opening line or a member of the root
+/// value, whereas one before the closing line of a member doesn't.
+struct Flushing<W: Write> {
+    inner: BufWriter<W>,
+    flush: Flush,
+    level: Rc<Cell<usize>>,
+    /// The level of the line that was started last, as far as seen here.
+    line: usize,
+    unflushed: usize,
+}
+
+impl<W> Write for Flushing<W>
+where W: Write {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.inner.write_all(bytes)?;
+        self.unflushed += bytes.len();
+        let flush = match self.flush {
+            Flush::PerItem if bytes.contains(&b'\n') => {
+                let ended = mem::replace(&mut self.line, self.level.get());
+                ended <= 1 && self.line <= 1
+            },
+            Flush::PerItem => false,
+            Flush::Bytes(bytes) => self.unflushed >= bytes,
+            Flush::Never => false,
+        };
+        if flush {
+            self.flush()?;
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.unflushed = 0;
+        self.inner.flush()
+    }
+}
@@ -1,154 +1,2047 @@
-use crate::{Newline, Style, Styles};
-use crate::error::{StringifyResult};
-use std::collections::{BTreeMap, HashMap};
+use crate::{
+    Entry, EntryOrder, Grouping, Layout, Multiline, Newline, NoneStyle,
+    Notation, Quoting, Radix, Role, Style, StyleKey, Styles, TrailingSeparator
+};
+use crate::budget::Budget;
+use crate::context::{PathSegment, StringifyContext};
+use crate::display::Stringified;
+use crate::dynamic::StringifyDyn;
+use crate::fmt_writer::FmtWriter;
+use crate::layout::{display_width, ColumnWriter, FitWriter, LineStartWriter, PrefixWriter, TrimWriter};
+use crate::node::Node;
+use crate::error::{StringifyError, StringifyResult};
+use std::collections::{
+    BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque
+};
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::fmt;
 use std::hash::Hash;
 use std::io::Write;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, RwLock, TryLockError};
 
 
 pub trait Stringify2 {
     /// Stringify a datum. To achieve this, there are a number of
     /// knobs that can be twisted to achieve the desired result:
-    /// - `parent_init` and `parent_rest` are the Styles used for `self`:
-    ///   + `parent_init` is used at the start of stringifying `self`
-    ///   + `parent_rest` is used everywhere else during the
-    ///     stringification of `self`
-    /// - `child_init` and `child_rest` are auxiliary Styles
-    ///   + `child_init` is used at the start of stringifying some
-    ///     internal component of `self`
-    ///   + `child_rest` is used everywhere else during the
-    ///     stringification of `self`
     /// - `buf` is the buffer to which to write the stringification.
-    fn stringify<W>(&self, buf: &mut W, styles: &Styles) -> StringifyResult<()>
+    /// - `styles` are the named `Style`s and the `Options` to use.
+    ///   Which names are looked up depends on the implementation,
+    ///   e.g. `"start"` before a container label and `"end"` before
+    ///   each of its elements and its closing delimiter.
+    /// - `ctx` tracks the depth and path of `self` in the root value.
+    ///   The indentation level of a `Style` with `Newline::Add` is
+    ///   relative to `ctx.depth()`, so implementations stringify their
+    ///   components with `ctx.nested(..)`, or `ctx.inline(..)` for those
+    ///   written inline, and `stringify_component` rather than adjusting
+    ///   the indentation level of a `Style` by hand.
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
     where W: Write;
 
     fn stringify_new(&self, styles: &Styles) -> StringifyResult<String> {
-        let mut buf = String::new();
-        self.stringify(unsafe { buf.as_mut_vec() }, styles)?;
+        self.stringify_new_in(String::new(), styles)
+    }
+
+    /// Like `stringify_new`, but reuse the allocation of `buf`, e.g. of
+    /// a `String` taken from a `BufferPool`. `buf` is cleared first.
+    fn stringify_new_in(&self, mut buf: String, styles: &Styles) -> StringifyResult<String> {
+        buf.clear();
+        buf.reserve(self.size_hint());
+        self.stringify_fmt(&mut buf, styles)?;
         Ok(buf)
     }
 
+    /// An estimate of the number of bytes of the stringification of `self`,
+    /// which `stringify_new` reserves up front. It needn't be exact, but
+    /// it should be cheap to compute. By default there is no estimate, i.e. 0.
+    fn size_hint(&self) -> usize {
+        0
+    }
+
+    /// Whether `self` is an empty or default value, e.g. `None`, an empty
+    /// collection or string, 0 or `false`, which `Options::skip_unset`
+    /// leaves out where it is the value of a field. By default, `false`.
+    fn is_unset(&self) -> bool {
+        false
+    }
+
+    /// Describe `self` as a `Node`, which can be post-processed and then
+    /// written in any format with a `Render`er. Containers describe their
+    /// components with `Node::of`. By default, `self` is described by its
+    /// compact stringification, as a `Node::Text`.
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        Ok(Node::Text(self.stringify_compact(styles)?))
+    }
+
+    /// Stringify `self` on a single line, i.e. with `Layout::Compact`
+    /// regardless of the `Layout` in the `Options` of `styles`.
+    fn stringify_compact(&self, styles: &Styles) -> StringifyResult<String> {
+        let options = styles.options().clone().with_layout(Layout::Compact);
+        self.stringify_new(&styles.clone().with_options(options))
+    }
+
+    /// Stringify `self` as a root value to a `fmt::Write` such as a
+    /// `String` or a `fmt::Formatter`, rather than to an `io::Write`.
+    fn stringify_fmt<F>(&self, buf: &mut F, styles: &Styles) -> StringifyResult<()>
+    where F: fmt::Write {
+        let mut writer = FmtWriter::new(buf);
+        self.stringify_root(&mut writer, styles)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Borrow `self` together with `styles` as a value that implements
+    /// `Display`, which stringifies `self` only when it is formatted.
+    fn display_with<'a>(&'a self, styles: &'a Styles) -> Stringified<'a, Self> {
+        Stringified::new(self, styles)
+    }
+
+    /// Stringify `self` as the root of a stringification. If the `Options`
+    /// of `styles` set `max_bytes`, the stringification stops once that
+    /// many bytes have been written, and a truncation notice is appended
+    /// e.g. `… (truncated)`. With `Layout::Fit`, the column that the output
+    /// has reached is tracked in the `StringifyContext`.
+    fn stringify_root<W>(&self, buf: &mut W, styles: &Styles) -> StringifyResult<()>
+    where W: Write {
+        stringify_root_in(self, buf, styles, &mut StringifyContext::new())
+    }
+
     /// Convenience method to help stringify an enum variant / struct field.
+    /// The `value` is stringified one level deeper than `self`.
     fn stringify_field<V, W>(&self,
                              buf: &mut W,
                              styles: &Styles,
+                             ctx: &mut StringifyContext,
                              name: &str,
                              value: &V) -> StringifyResult<()>
-    where V: Stringify2,
+    where V: Stringify2 + ?Sized,
           W: Write {
-        let name_style: Style = styles.get("name")?;
-        self.indent(buf, name_style)?;
-        buf.write_all(name.as_bytes())?;
-        buf.write_all("=".as_bytes())?;
-        value.stringify(buf, styles)?;
+        let name_style: Style = styles.get_or_default(StyleKey::Name);
+        self.indent(buf, styles, ctx, &name_style)?;
+        styles.options().theme.write(buf, Role::FieldName, name)?;
+        write_alignment(buf, styles, ctx, display_width(name), ctx.name_width())?;
+        buf.write_all(styles.options().delimiters.field_value.as_bytes())?;
+        ctx.nested(PathSegment::Field(name.to_string()), |ctx| {
+            value.stringify_component(buf, styles, ctx)
+        })
+    }
+
+    /// Convenience method to stringify `self` as a struct or enum variant
+    /// named `label` with the given named `fields`, as the derive does:
+    /// ```text
+    /// self.stringify_fields(buf, styles, ctx, "Point", &[("x", &self.x), ("y", &self.y)])
+    /// ```
+    /// Each field is written with `stringify_field`, followed by a separator,
+    /// except for unset fields with `Options::skip_unset`. Without any fields
+    /// to write, it is written as `label {}`.
+    fn stringify_fields<W>(&self,
+                           buf: &mut W,
+                           styles: &Styles,
+                           ctx: &mut StringifyContext,
+                           label: &str,
+                           fields: &[(&str, &dyn StringifyDyn)]) -> StringifyResult<()>
+    where W: Write {
+        let theme = styles.options().theme;
+        let skip_unset = styles.options().skip_unset;
+        let written = |(_, value): &&(&str, &dyn StringifyDyn)| !(skip_unset && value.is_unset_dyn());
+        let count = fields.iter().filter(written).count();
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+        if count == 0 {
+            theme.write(buf, Role::Container, &format!("{} {{}}", label))?;
+            return Ok(());
+        }
+        theme.write(buf, Role::Container, &format!("{} {{", label))?;
+        let width = __name_width(fields.iter().map(|field| (field.0, written(&field))));
+        ctx.with_name_width(width, |ctx| {
+            for (idx, (name, value)) in fields.iter().filter(written).enumerate() {
+                self.stringify_field(buf, styles, ctx, name, *value)?;
+                self.write_separator(buf, styles, ctx, idx + 1 == count)?;
+            }
+            Ok::<_, StringifyError>(())
+        })?;
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::End))?;
+        theme.write(buf, Role::Container, "}")?;
+        Ok(())
+    }
+
+    /// Stringify the fields of `self` as if they were fields of the value
+    /// that contains `self` as its field `name`, as `#[stringify(flatten)]`
+    /// does when deriving. Types without named fields are written as
+    /// an ordinary field, which is the default. `last` tells whether
+    /// `self` is the last field of the value that contains it.
+    fn stringify_flattened<W>(&self,
+                              buf: &mut W,
+                              styles: &Styles,
+                              ctx: &mut StringifyContext,
+                              name: &str,
+                              last: bool) -> StringifyResult<()>
+    where W: Write {
+        self.stringify_field(buf, styles, ctx, name, self)?;
+        self.write_separator(buf, styles, ctx, last)
+    }
+
+    /// Write the separator after an element or field of `self`. If it is
+    /// the `last` one, the `TrailingSeparator` policy in the `Options` of
+    /// `styles` decides whether to write it, where the closing delimiter
+    /// is on a line of its own if the `"end"` style starts a new line.
+    /// With `Layout::Compact`, or if `ctx` is flat, the separator is
+    /// followed by a space instead, and left out after the last element.
+    fn write_separator<W>(&self,
+                          buf: &mut W,
+                          styles: &Styles,
+                          ctx: &StringifyContext,
+                          last: bool) -> StringifyResult<()>
+    where W: Write {
+        let options = styles.options();
+        if options.layout == Layout::Compact || ctx.is_flat() {
+            if !last {
+                buf.write_all(options.delimiters.separator.as_bytes())?;
+                buf.write_all(" ".as_bytes())?;
+            }
+            return Ok(());
+        }
+        let write = !last || match options.trailing {
+            TrailingSeparator::Always => true,
+            TrailingSeparator::Never => false,
+            TrailingSeparator::OnlyMultiline =>
+                styles.get_or_default(StyleKey::End).newline.count() > 0,
+        };
+        if write {
+            buf.write_all(options.delimiters.separator.as_bytes())?;
+        }
         Ok(())
     }
 
+    /// Stringify `self` as a component of an enclosing value, within
+    /// `ctx.nested(..)` or `ctx.inline(..)`. Beyond the `max_depth` of the `Options`, the
+    /// `elision` marker is written instead of `self`. If a selector of
+    /// `styles` matches the path of `self`, its overrides are applied,
+    /// and a formatter in the `Options` for the type of `self` takes
+    /// precedence over `stringify`.
+    fn stringify_component<W>(&self,
+                              buf: &mut W,
+                              styles: &Styles,
+                              ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let options = styles.options();
+        if let Some(limit) = options.depth_limit {
+            if ctx.depth() > limit {
+                return Err(StringifyError::DepthLimitExceeded { limit, path: ctx.path_string() });
+            }
+        }
+        let result = match options.max_depth {
+            Some(max_depth) if ctx.depth() > max_depth => {
+                options.theme.write(buf, Role::Container, options.elision.text())?;
+                Ok(())
+            },
+            _ => match styles.select(ctx.path()) {
+                Some(overrides) => styles.scoped(overrides.clone(), |styles| {
+                    stringify_fitted(self, buf, styles, ctx)
+                }),
+                None => stringify_fitted(self, buf, styles, ctx),
+            },
+        };
+        result.map_err(|err| err.at(ctx))
+    }
+
+    /// Return `Some(self)` to let the `Formatters` in the `Options` replace
+    /// the rendering of `self`. This is only possible for `'static` types,
+    /// so it returns `None` by default.
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+
     /// Convenience method to help stringify a primitive.
     fn stringify_primitive<W>(&self, buf: &mut W) -> StringifyResult<()>
     where W: Write {
-        self.stringify(buf, &styles! { })
+        self.stringify(buf, &styles! { }, &mut StringifyContext::new())
     }
 
     fn stringify_primitive_new(&self) -> StringifyResult<String> {
         let mut buf = String::new();
-        self.stringify_primitive(unsafe { buf.as_mut_vec() })?;
+        let mut writer = FmtWriter::new(&mut buf);
+        self.stringify_primitive(&mut writer)?;
+        writer.finish()?;
         Ok(buf)
     }
 
-    /// If `style.newline` == `Newline::Add`, write a newline.
-    /// Then, regardless of whether or not a newline was written,
-    /// apply `style.indent` exactly `style.indent_level` times.
-    fn indent<W>(&self, buf: &mut W, style: Style) -> StringifyResult<()>
+    /// If `style.newline` == `Newline::Add`, write the `LineEnding` of the
+    /// `Options` of `styles` (or as many as `Newline::Count` asks for)
+    /// followed by the indentation of level `ctx.depth() + style.indent_level`.
+    /// Otherwise the current line is continued, and only the indentation
+    /// of level `style.indent_level` is written.
+    /// Each level is indented with `style.indent` by default, but the
+    /// `IndentPolicy` in the `Options` of `styles` can choose a different
+    /// unit per level, and the `Guides` in the `Options` can draw guide
+    /// rails in them. With `Layout::Compact`, or if `ctx` is flat,
+    /// nothing is written at all.
+    fn indent<W>(&self,
+                 buf: &mut W,
+                 styles: &Styles,
+                 ctx: &StringifyContext,
+                 style: &Style) -> StringifyResult<()>
     where W: Write {
-        if style.newline == Newline::Add { buf.write_all("\n".as_bytes())?; }
-        for _ in 0 .. style.indent_level {
-            buf.write_all(style.indent.as_bytes())?;
+        let options = styles.options();
+        if options.layout == Layout::Compact || ctx.is_flat() {
+            return Ok(());
         }
+        let newlines = style.newline.count();
+        let levels = match newlines {
+            0 => style.indent_level,
+            _ => ctx.depth() + style.indent_level,
+        };
+        if newlines > 0 {
+            ctx.line_level_cell().set(levels);
+        }
+        for _ in 0 .. newlines {
+            buf.write_all(options.line_ending.as_str().as_bytes())?;
+        }
+        let indenting = ctx.indenting_cell();
+        indenting.set(true);
+        let result = ctx.padding(options, style, levels, |padding| buf.write_all(padding.as_bytes()));
+        indenting.set(false);
+        result?;
         Ok(())
     }
 }
 
 
+/// The number of bytes that a size hint reserves per line for its
+/// indentation, separator and line ending.
+const LINE_HINT: usize = 8;
+
+/// The size hint of a float, which is typically written with up to
+/// 17 significant digits.
+const FLOAT_HINT: usize = 20;
+
+/// The size hint of a sequence-like container named `label` with the `items`.
+pub(crate) fn seq_hint<'i, T, I>(label: &str, items: I) -> usize
+where T: Stringify2 + 'i,
+      I: IntoIterator<Item = &'i T> {
+    label.len() + 4 + items.into_iter().map(|item| item.size_hint() + LINE_HINT).sum::<usize>()
+}
+
+/// The size hint of a map-like container named `label` with the `entries`.
+fn map_hint<'i, K, V, I>(label: &str, entries: I) -> usize
+where K: Stringify2 + 'i,
+      V: Stringify2 + 'i,
+      I: IntoIterator<Item = (&'i K, &'i V)> {
+    label.len() + 4 + entries.into_iter()
+        .map(|(key, value)| key.size_hint() + 3 + value.size_hint() + LINE_HINT)
+        .sum::<usize>()
+}
+
+/// Describe a map-like container named `label` with the `entries`.
+fn map_node<'i, K, V, I>(label: &'static str, styles: &Styles, entries: I) -> StringifyResult<Node>
+where K: Stringify2 + 'i,
+      V: Stringify2 + 'i,
+      I: IntoIterator<Item = (&'i K, &'i V)> {
+    let entries = entries.into_iter()
+        .map(|(key, value)| Ok((Node::of(key, styles)?, Node::of(value, styles)?)))
+        .collect::<StringifyResult<_>>()?;
+    Ok(Node::Map { label: Cow::Borrowed(label), entries })
+}
+
+/// Describe a sequence-like container named `label` with the `items`.
+pub(crate) fn seq_node<'i, T, I>(label: &'static str, styles: &Styles, items: I) -> StringifyResult<Node>
+where T: Stringify2 + 'i,
+      I: IntoIterator<Item = &'i T> {
+    Ok(Node::Seq { label: Cow::Borrowed(label), items: item_nodes(styles, items)? })
+}
+
+/// Describe a set-like container named `label` with the `items`.
+fn set_node<'i, T, I>(label: &'static str, styles: &Styles, items: I) -> StringifyResult<Node>
+where T: Stringify2 + 'i,
+      I: IntoIterator<Item = &'i T> {
+    Ok(Node::Set { label: Cow::Borrowed(label), items: item_nodes(styles, items)? })
+}
+
+fn item_nodes<'i, T, I>(styles: &Styles, items: I) -> StringifyResult<Vec<Node>>
+where T: Stringify2 + 'i,
+      I: IntoIterator<Item = &'i T> {
+    items.into_iter().map(|item| Node::of(item, styles)).collect()
+}
+
+fn tuple_node(label: &'static str, items: Vec<Node>) -> StringifyResult<Node> {
+    Ok(Node::Tuple { label: Cow::Borrowed(label), items })
+}
+
+/// Whether `value` is the default value of its type, for fields with
+/// `#[stringify(skip_if_default)]`.
+#[doc(hidden)]
+pub fn __is_default<T>(value: &T) -> bool
+where T: Default + PartialEq {
+    *value == T::default()
+}
+
+/// The width of the widest of the field `names` that are written, which
+/// `Options::align` pads them to, where each name comes with whether its
+/// field is written, so that fields that are left out don't widen it.
+#[doc(hidden)]
+pub fn __name_width<'n, I>(names: I) -> usize
+where I: IntoIterator<Item = (&'n str, bool)> {
+    names.into_iter()
+        .filter(|&(_, written)| written)
+        .map(|(name, _)| display_width(name))
+        .max().unwrap_or(0)
+}
+
+/// Stringify `node` like the value that it describes (see `Node`).
+pub(crate) fn stringify_node<W>(node: &Node,
+                                buf: &mut W,
+                                styles: &Styles,
+                                ctx: &mut StringifyContext) -> StringifyResult<()>
+where W: Write {
+    let theme = styles.options().theme;
+    match node {
+        Node::Null => match styles.options().none {
+            NoneStyle::Print => theme.write(buf, Role::Container, "None")?,
+            NoneStyle::Omit => {},
+        },
+        Node::Bool(value) => value.stringify(buf, styles, ctx)?,
+        Node::Number(number) => match (number.parse::<i128>(), number.parse::<u128>()) {
+            (Ok(int), _) => write_integer(buf, styles, int)?,
+            (_, Ok(uint)) => write_integer(buf, styles, uint)?,
+            _ => theme.write(buf, Role::Number, number)?,
+        },
+        Node::String(text) => write_text(buf, styles, ctx, text, '"')?,
+        Node::Char(c) => c.stringify(buf, styles, ctx)?,
+        Node::Text(text) => buf.write_all(text.as_bytes())?,
+        Node::Unit(label) => {
+            node.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+            theme.write(buf, Role::Container, label)?;
+        },
+        Node::Seq { label, items } => stringify_seq(node, buf, styles, ctx, label, items)?,
+        Node::Set { label, items } => stringify_set(node, buf, styles, ctx, label, items)?,
+        Node::Tuple { label, items } if label.is_empty() => {
+            let end: Style = styles.get_or_default(StyleKey::End);
+            let (open, close) = &styles.options().delimiters.tuple;
+            node.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+            theme.write(buf, Role::Container, open)?;
+            for (idx, item) in items.iter().enumerate() {
+                ctx.nested(PathSegment::Index(idx), |ctx| {
+                    node.indent(buf, styles, ctx, &end)?;
+                    item.stringify_component(buf, styles, ctx)
+                })?;
+                node.write_separator(buf, styles, ctx, idx + 1 == items.len())?;
+            }
+            if !items.is_empty() {
+                node.indent(buf, styles, ctx, &end)?;
+            }
+            theme.write(buf, Role::Container, close)?;
+        },
+        Node::Tuple { label, items } => {
+            node.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+            theme.write(buf, Role::Container, &format!("{}(", label))?;
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    buf.write_all(styles.options().delimiters.separator.as_bytes())?;
+                    buf.write_all(" ".as_bytes())?;
+                }
+                ctx.inline(PathSegment::Index(idx), |ctx| {
+                    item.stringify_component(buf, styles, ctx)
+                })?;
+            }
+            theme.write(buf, Role::Container, ")")?;
+        },
+        Node::Map { label, entries } => {
+            let entries = entries.iter().map(|(key, value)| (key, value));
+            stringify_map(node, buf, styles, ctx, label, entries)?
+        },
+        Node::Struct { label, fields } => {
+            let fields: Vec<(&str, &dyn StringifyDyn)> = fields.iter()
+                .map(|(name, value)| (name.as_ref(), value as &dyn StringifyDyn))
+                .collect();
+            node.stringify_fields(buf, styles, ctx, label, &fields)?;
+        },
+    }
+    Ok(())
+}
+
+/// Stringify `this` as a root value (see `Stringify2::stringify_root`)
+/// in `ctx`, whose line level a writer further down may follow, e.g. to
+/// flush between the members of `this` in `Stringifier::stream`.
+pub(crate) fn stringify_root_in<S, W>(this: &S,
+                                      buf: &mut W,
+                                      styles: &Styles,
+                                      ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    if !styles.options().line_numbers {
+        return stringify_lines(this, buf, styles, ctx);
+    }
+    let mut lines = vec![];
+    stringify_lines(this, &mut lines, styles, ctx)?;
+    write_numbered(buf, &lines)
+}
+
+/// Stringify `this` as a root value, with the `LinePrefix` of the `Options`
+/// before every line, and for canonical output without indentation at the
+/// end of lines.
+fn stringify_lines<S, W>(this: &S,
+                         buf: &mut W,
+                         styles: &Styles,
+                         ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let mut tracked = LineStartWriter::new(buf, ctx.indenting_cell(), ctx.line_started_cell());
+    match styles.options().canonical {
+        true => {
+            let mut trimmed = TrimWriter::new(&mut tracked, ctx.indenting_cell());
+            stringify_prefixed(this, &mut trimmed, styles, ctx)
+        },
+        false => stringify_prefixed(this, &mut tracked, styles, ctx),
+    }
+}
+
+/// Stringify `this` as a root value, with the `LinePrefix` of the `Options`
+/// before every line.
+fn stringify_prefixed<S, W>(this: &S,
+                            buf: &mut W,
+                            styles: &Styles,
+                            ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let prefix = &styles.options().line_prefix;
+    match prefix.is_none() {
+        true => stringify_tracked(this, buf, styles, ctx),
+        false => {
+            let mut writer = PrefixWriter::new(buf, prefix, ctx.line_level_cell(), ctx.indenting_cell());
+            stringify_tracked(this, &mut writer, styles, ctx)
+        },
+    }
+}
+
+/// Write the `output` of a stringification with the number of each line
+/// in a gutter, which is as wide as the number of the last line, e.g.
+/// ` 9 | ` and `10 | `. Empty lines get no space after the gutter.
+fn write_numbered<W>(buf: &mut W, output: &[u8]) -> StringifyResult<()>
+where W: Write {
+    let count = output.split_inclusive(|&byte| byte == b'\n').count();
+    let width = count.checked_ilog10().map_or(1, |log| log as usize + 1);
+    for (idx, line) in output.split_inclusive(|&byte| byte == b'\n').enumerate() {
+        write!(buf, "{:>1$} |", idx + 1, width)?;
+        if !matches!(line, b"\n" | b"\r\n") {
+            buf.write_all(b" ")?;
+        }
+        buf.write_all(line)?;
+    }
+    Ok(())
+}
+
+/// Stringify `this` as a root value, while tracking the column that
+/// the output has reached for `Layout::Fit`.
+fn stringify_tracked<S, W>(this: &S,
+                           buf: &mut W,
+                           styles: &Styles,
+                           ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    match styles.options().layout {
+        Layout::Fit { .. } => {
+            let mut writer = ColumnWriter::new(buf, ctx.column_cell());
+            stringify_budgeted(this, &mut writer, styles, ctx)
+        },
+        _ => stringify_budgeted(this, buf, styles, ctx),
+    }
+}
+
+/// Stringify `this` as a root value within the `max_bytes` of the
+/// `Options` of `styles`, if any (see `Stringify2::stringify_root`).
+fn stringify_budgeted<S, W>(this: &S,
+                            buf: &mut W,
+                            styles: &Styles,
+                            ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let options = styles.options();
+    let max_bytes = match options.max_bytes {
+        Some(max_bytes) => max_bytes,
+        None => return stringify_fitted(this, buf, styles, ctx),
+    };
+    let mut budget = Budget::new(buf, max_bytes);
+    match stringify_fitted(this, &mut budget, styles, ctx) {
+        Err(_) if budget.is_exhausted() => {
+            let notice = format!("{} (truncated)", options.elision.text());
+            options.theme.write(budget.inner(), Role::Container, &notice)?;
+            Ok(())
+        },
+        result => result,
+    }
+}
+
+/// Stringify `this` according to the `Layout` in the `Options` of `styles`.
+/// With `Layout::Fit`, `this` is first written flat, i.e. on a single line,
+/// to a scratch buffer. If that fits within the width that is left on the
+/// current line it is copied to `buf`, and otherwise `this` is written in
+/// the expanded form, in which its components get the same treatment.
+fn stringify_fitted<S, W>(this: &S,
+                          buf: &mut W,
+                          styles: &Styles,
+                          ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let width = match styles.options().layout {
+        Layout::Fit { width } if !ctx.is_flat() => width,
+        _ => return stringify_formatted(this, buf, styles, ctx),
+    };
+    let mut fit = FitWriter::new(ctx.column(), width);
+    let flat = ctx.flat(|ctx| stringify_formatted(this, &mut fit, styles, ctx));
+    match fit.into_line() {
+        Some(line) => {
+            flat?;
+            buf.write_all(&line)?;
+            Ok(())
+        },
+        None => stringify_formatted(this, buf, styles, ctx),
+    }
+}
+
+/// Stringify `this` with the formatter for its type in the `Options`
+/// of `styles`, or with its own `Stringify2` impl if there is none.
+fn stringify_formatted<S, W>(this: &S,
+                             buf: &mut W,
+                             styles: &Styles,
+                             ctx: &mut StringifyContext) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let formatters = &styles.options().formatters;
+    if !formatters.is_empty() {
+        if let Some(text) = this.as_any().and_then(|any| formatters.format(any)) {
+            buf.write_all(text.as_bytes())?;
+            return Ok(());
+        }
+    }
+    this.stringify(buf, styles, ctx)
+}
+
+/// Stringify a map-like container: `label` followed by the `entries`
+/// between curly braces. An empty map is written as `label {}`.
+/// The `labels` of the `Options` may replace or omit the `label`.
+/// Each entry is written with the `"key"` style (by default the `"end"`
+/// style) before its key and the `"value"` style, if any, before its value.
+/// The `styles` are passed on to the keys and values as they are.
+fn stringify_map<'i, S, K, V, I, W>(this: &S,
+                                    buf: &mut W,
+                                    styles: &Styles,
+                                    ctx: &mut StringifyContext,
+                                    label: &str,
+                                    entries: I) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      K: Stringify2 + 'i,
+      V: Stringify2 + 'i,
+      I: IntoIterator<Item = (&'i K, &'i V)>,
+      W: Write {
+    let labels = &styles.options().labels;
+    let delimiters = &styles.options().delimiters;
+    let (open, close) = &delimiters.map;
+    let entries: Vec<(&K, &V)> = entries.into_iter().collect();
+    if entries.is_empty() {
+        let empty = format!("{}{}", open, close);
+        styles.options().theme.write(buf, Role::Container, &labels.open(label, &empty))?;
+        return Ok(());
+    }
+    let end: Style = styles.get_or_default(StyleKey::End);
+    let key_style: Style = styles.get_or(StyleKey::Key, end.clone());
+    let value_style: Option<Style> = styles.get(StyleKey::Value).ok();
+    this.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+    let opening = open_sized(styles, label, open, entries.len(), ENTRIES);
+    styles.options().theme.write(buf, Role::Container, &opening)?;
+    let max_elements = styles.options().max_elements.unwrap_or(usize::MAX);
+    let keys = entries.iter().take(max_elements).map(|(key, _)| *key);
+    let key_width = aligned_width(styles, ctx, keys)?;
+    let mut entries = entries.into_iter().peekable();
+    for _ in 0 .. max_elements {
+        let (key, value) = match entries.next() {
+            Some(entry) => entry,
+            None => break,
+        };
+        let segment = PathSegment::Key(key.stringify_primitive_new()?);
+        ctx.nested(segment, |ctx| {
+            this.indent(buf, styles, ctx, &key_style)?;
+            match key_width {
+                0 => key.stringify(buf, styles, ctx)?,
+                _ => {
+                    let mut text = vec![];
+                    key.stringify(&mut text, styles, ctx)?;
+                    buf.write_all(&text)?;
+                    let width = display_width(&String::from_utf8_lossy(&text));
+                    write_alignment(buf, styles, ctx, width, key_width)?;
+                },
+            }
+            buf.write_all(delimiters.key_value.as_bytes())?;
+            if let Some(value_style) = &value_style {
+                this.indent(buf, styles, ctx, value_style)?;
+            }
+            value.stringify_component(buf, styles, ctx)
+        })?;
+        this.write_separator(buf, styles, ctx, entries.peek().is_none())?;
+    }
+    write_remainder(this, buf, styles, ctx, &end, entries.count())?;
+    this.indent(buf, styles, ctx, &end)?;
+    styles.options().theme.write(buf, Role::Container, close)?;
+    Ok(())
+}
+
+/// The width of the widest of the `keys` when they are written flat, if
+/// they are aligned with `Options::align` (see `write_alignment`), and 0
+/// otherwise.
+fn aligned_width<'i, K, I>(styles: &Styles,
+                           ctx: &mut StringifyContext,
+                           keys: I) -> StringifyResult<usize>
+where K: Stringify2 + ?Sized + 'i,
+      I: IntoIterator<Item = &'i K> {
+    let options = styles.options();
+    if !options.align || options.layout == Layout::Compact || ctx.is_flat() {
+        return Ok(0);
+    }
+    let mut widest = 0;
+    for key in keys {
+        let mut text = vec![];
+        ctx.flat(|ctx| key.stringify(&mut text, styles, ctx))?;
+        widest = widest.max(display_width(&String::from_utf8_lossy(&text)));
+    }
+    Ok(widest)
+}
+
+/// With `Options::align`, pad a field name or key that was just written
+/// in `width` columns to `aligned` columns, unless `ctx` is flat.
+fn write_alignment<W>(buf: &mut W,
+                      styles: &Styles,
+                      ctx: &StringifyContext,
+                      width: usize,
+                      aligned: usize) -> StringifyResult<()>
+where W: Write {
+    match styles.options().align {
+        true => write_padding(buf, styles, ctx, width, aligned),
+        false => Ok(()),
+    }
+}
+
+/// Pad a field name or key that was just written in `width` columns
+/// to `aligned` columns, unless the layout is compact or `ctx` is flat.
+pub(crate) fn write_padding<W>(buf: &mut W,
+                               styles: &Styles,
+                               ctx: &StringifyContext,
+                               width: usize,
+                               aligned: usize) -> StringifyResult<()>
+where W: Write {
+    if styles.options().layout != Layout::Compact && !ctx.is_flat() && width < aligned {
+        write!(buf, "{:1$}", "", aligned - width)?;
+    }
+    Ok(())
+}
+
+/// If `remaining` > 0, write a marker for the `remaining` elements of a
+/// container that were left out because of the `max_elements` option,
+/// e.g. `… (4321 more)`. The marker is indented like the elements.
+pub(crate) fn write_remainder<S, W>(this: &S,
+                                    buf: &mut W,
+                                    styles: &Styles,
+                                    ctx: &mut StringifyContext,
+                                    end: &Style,
+                                    remaining: usize) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    if remaining == 0 {
+        return Ok(());
+    }
+    let options = styles.options();
+    this.indent(buf, styles, &ctx.indented(), end)?;
+    let marker = format!("{} ({} more)", options.elision.text(), remaining);
+    options.theme.write(buf, Role::Container, &marker)?;
+    Ok(())
+}
+
+/// Sort `entries` according to `order`. Keys are compared by their
+/// stringification, and so are values, where `value` is used to stringify
+/// the second component of an entry. For `EntryOrder::Sorted`, entries
+/// with the same key text are ordered by their value text.
+fn sort_entries<'i, K, X, I, F>(styles: &Styles,
+                                order: &EntryOrder,
+                                entries: I,
+                                value: F) -> StringifyResult<Vec<(&'i K, X)>>
+where K: Stringify2 + ?Sized + 'i,
+      I: IntoIterator<Item = (&'i K, X)>,
+      F: Fn(&X) -> StringifyResult<String> {
+    let mut keyed = vec![];
+    for (key, extra) in entries {
+        let value = match order {
+            EntryOrder::Iteration => String::new(),
+            _ => value(&extra)?,
+        };
+        keyed.push((key.stringify_new(styles)?, value, key, extra));
+    }
+    match order {
+        EntryOrder::Iteration => {},
+        EntryOrder::Sorted => keyed.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1))),
+        EntryOrder::Custom(comparator) => keyed.sort_by(|a, b| comparator.compare(
+            &Entry { key: &a.0, value: &a.1 },
+            &Entry { key: &b.0, value: &b.1 },
+        )),
+    }
+    Ok(keyed.into_iter().map(|(_, _, key, extra)| (key, extra)).collect())
+}
+
 impl<K, V> Stringify2 for HashMap<K, V>
 where K: Stringify2 + Eq + Hash,
       V: Stringify2 {
-    fn stringify<W>(&self, buf: &mut W, styles: &Styles) -> StringifyResult<()>
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
     where W: Write {
-        if self.is_empty() {
-            buf.write_all("HashMap {}".as_bytes())?;
-            return Ok(());
+        match &styles.options().order {
+            EntryOrder::Iteration =>
+                stringify_map(self, buf, styles, ctx, "HashMap", self.iter()),
+            order => {
+                let entries = sort_entries(
+                    styles, order, self.iter(), |value| value.stringify_new(styles)
+                )?;
+                stringify_map(self, buf, styles, ctx, "HashMap", entries)
+            },
         }
-        let start: Style = styles.get("start")?;
-        self.indent(buf, start)?;
-        buf.write_all("HashMap {".as_bytes())?;
-        for (key, value) in self.iter() {
-            key.stringify(buf, &styles! {
-                "key" => Style::standard(Newline::Add, start.indent_level + 1)
-            })?;
-            buf.write_all(" : ".as_bytes())?;
-            value.stringify(buf, &styles! {
-                "value" => Style::standard(Newline::Add, start.indent_level + 1)
-            })?;
-            buf.write_all(",".as_bytes())?;
-        }
-        self.indent(buf, Style::standard(
-            Newline::Add,
-            styles.get("end")?.indent_level + 1
-        ))?;
-        buf.write_all("}".as_bytes())?;
-        Ok(())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        let entries = sort_entries(
+            styles, &styles.options().order, self.iter(), |value| value.stringify_new(styles)
+        )?;
+        map_node("HashMap", styles, entries)
+    }
+
+    fn size_hint(&self) -> usize {
+        map_hint("HashMap", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
     }
 }
 
 impl<K, V> Stringify2 for BTreeMap<K, V>
 where K: Stringify2 + Eq + Hash,
       V: Stringify2 {
-    fn stringify<W>(&self, buf: &mut W, styles: &Styles) -> StringifyResult<()>
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
     where W: Write {
-        if self.is_empty() {
-            buf.write_all("BTreeMap {}".as_bytes())?;
-            return Ok(());
+        match &styles.options().order {
+            order @ EntryOrder::Custom(_) => {
+                let entries = sort_entries(
+                    styles, order, self.iter(), |value| value.stringify_new(styles)
+                )?;
+                stringify_map(self, buf, styles, ctx, "BTreeMap", entries)
+            },
+            _ => stringify_map(self, buf, styles, ctx, "BTreeMap", self.iter()),
         }
-        let start: Style = styles.get("start")?;
-        self.indent(buf, start)?;
-        buf.write_all("BTreeMap {".as_bytes())?;
-        for (key, value) in self.iter() {
-            key.stringify(buf, &styles! {
-                "key" => Style::standard(Newline::Add, start.indent_level + 1)
-            })?;
-            buf.write_all(" : ".as_bytes())?;
-            value.stringify(buf, &styles! {
-                "value" => Style::standard(Newline::Add, start.indent_level + 1)
-            })?;
-            buf.write_all(",".as_bytes())?;
-        }
-        self.indent(buf, Style::standard(
-            Newline::Add,
-            styles.get("end")?.indent_level + 1
-        ))?;
-        buf.write_all("}".as_bytes())?;
-        Ok(())
     }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        match &styles.options().order {
+            order @ EntryOrder::Custom(_) => {
+                let entries = sort_entries(
+                    styles, order, self.iter(), |value| value.stringify_new(styles)
+                )?;
+                map_node("BTreeMap", styles, entries)
+            },
+            _ => map_node("BTreeMap", styles, self.iter()),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        map_hint("BTreeMap", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Stringify a sequence-like container: `label` followed by the `items`
+/// between square brackets, one item per line with the `"end"` style.
+/// An empty sequence is written as `label []`.
+/// The `labels` of the `Options` may replace or omit the `label`.
+pub(crate) fn stringify_seq<'i, S, T, I, W>(this: &S,
+                                            buf: &mut W,
+                                            styles: &Styles,
+                                            ctx: &mut StringifyContext,
+                                            label: &str,
+                                            items: I) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      T: Stringify2 + 'i,
+      I: IntoIterator<Item = &'i T>,
+      I::IntoIter: ExactSizeIterator,
+      W: Write {
+    let labels = &styles.options().labels;
+    let delimiters = &styles.options().delimiters;
+    let (open, close) = &delimiters.seq;
+    let mut items = items.into_iter().peekable();
+    if items.peek().is_none() {
+        let empty = format!("{}{}", open, close);
+        styles.options().theme.write(buf, Role::Container, &labels.open(label, &empty))?;
+        return Ok(());
+    }
+    if styles.options().grid && styles.options().layout != Layout::Compact && !ctx.is_flat() {
+        // The `Node` of `this` takes as much work as stringifying it, which
+        // would add up over nested sequences, so it is only built if the
+        // first row can be written as part of a grid.
+        let first = items.peek().map(|first| first.to_node(styles)).transpose()?;
+        if first.is_some_and(|first| is_grid(&[first], styles)) {
+            if let Node::Seq { items: rows, .. } = this.to_node(styles)? {
+                if is_grid(&rows, styles) {
+                    return stringify_grid(this, buf, styles, ctx, label, &rows);
+                }
+            }
+        }
+    }
+    let end: Style = styles.get_or_default(StyleKey::End);
+    this.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+    let opening = open_sized(styles, label, open, items.len(), ELEMENTS);
+    styles.options().theme.write(buf, Role::Container, &opening)?;
+    let max_elements = styles.options().max_elements.unwrap_or(usize::MAX);
+    for idx in 0 .. max_elements {
+        let item = match items.next() {
+            Some(item) => item,
+            None => break,
+        };
+        ctx.nested(PathSegment::Index(idx), |ctx| {
+            this.indent(buf, styles, ctx, &end)?;
+            write_index(buf, styles, idx)?;
+            item.stringify_component(buf, styles, ctx)
+        })?;
+        this.write_separator(buf, styles, ctx, items.peek().is_none())?;
+    }
+    write_remainder(this, buf, styles, ctx, &end, items.count())?;
+    this.indent(buf, styles, ctx, &end)?;
+    styles.options().theme.write(buf, Role::Container, close)?;
+    Ok(())
+}
+
+/// The singular and plural word for the elements of sequences and sets.
+pub(crate) const ELEMENTS: (&str, &str) = ("element", "elements");
+/// The singular and plural word for the entries of maps.
+const ENTRIES: (&str, &str) = ("entry", "entries");
+
+/// The opening of a non-empty container with `len` elements e.g. `Vec [`,
+/// which includes its size with `Options::sizes` as in `Vec (3 elements) [`,
+/// where `units` are the singular and plural word for the elements.
+pub(crate) fn open_sized(styles: &Styles,
+                         label: &str,
+                         open: &str,
+                         len: usize,
+                         units: (&str, &str)) -> String {
+    let options = styles.options();
+    if !options.sizes {
+        return options.labels.open(label, open);
+    }
+    let unit = match len {
+        1 => units.0,
+        _ => units.1,
+    };
+    options.labels.open(label, &format!("({} {}) {}", len, unit, open))
+}
+
+/// With `Options::indices`, write the index `idx` of an element of a
+/// sequence before the element, as in `[42]: `.
+pub(crate) fn write_index<W>(buf: &mut W, styles: &Styles, idx: usize) -> StringifyResult<()>
+where W: Write {
+    if styles.options().indices {
+        styles.options().theme.write(buf, Role::FieldName, &format!("[{}]", idx))?;
+        buf.write_all(b": ")?;
+    }
+    Ok(())
+}
+
+/// Return `true` if `rows` are sequences of scalars that can be written as
+/// a grid, i.e. if none of them has more than the `max_elements`, unless
+/// the elements are written with their indices.
+fn is_grid(rows: &[Node], styles: &Styles) -> bool {
+    let max_elements = styles.options().max_elements.unwrap_or(usize::MAX);
+    let indices = styles.options().indices;
+    !indices && rows.len() <= max_elements && rows.iter().all(|row| match row {
+        Node::Seq { items, .. } => items.len() <= max_elements && items.iter().all(|item| {
+            !matches!(item, Node::Seq { .. } | Node::Set { .. } | Node::Tuple { .. } | Node::Map { .. }
+                | Node::Struct { .. })
+        }),
+        _ => false,
+    })
+}
+
+/// Write the sequence `this` as a grid of its `rows` (see `Options::grid`).
+/// Each row is written on a line of its own, with the cells of each column
+/// padded to the width of the widest one. Columns of numbers are aligned
+/// to the right, and other columns to the left.
+fn stringify_grid<S, W>(this: &S,
+                        buf: &mut W,
+                        styles: &Styles,
+                        ctx: &mut StringifyContext,
+                        label: &str,
+                        rows: &[Node]) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      W: Write {
+    let options = styles.options();
+    let (open, close) = &options.delimiters.seq;
+    let mut cells: Vec<Vec<(String, bool)>> = vec![];
+    for row in rows {
+        let mut line = vec![];
+        if let Node::Seq { items, .. } = row {
+            for item in items {
+                let mut cell = vec![];
+                ctx.flat(|ctx| item.stringify(&mut cell, styles, ctx))?;
+                let number = matches!(item, Node::Number(_));
+                line.push((String::from_utf8_lossy(&cell).into_owned(), number));
+            }
+        }
+        cells.push(line);
+    }
+    let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0 .. columns).map(|col| {
+        cells.iter().filter_map(|line| line.get(col))
+            .map(|(cell, _)| display_width(cell))
+            .max().unwrap_or(0)
+    }).collect();
+    let right: Vec<bool> = (0 .. columns).map(|col| {
+        cells.iter().filter_map(|line| line.get(col)).all(|&(_, number)| number)
+    }).collect();
+    let end: Style = styles.get_or_default(StyleKey::End);
+    this.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+    let opening = open_sized(styles, label, open, rows.len(), ELEMENTS);
+    options.theme.write(buf, Role::Container, &opening)?;
+    for (idx, (row, line)) in rows.iter().zip(&cells).enumerate() {
+        let row_label = match row {
+            Node::Seq { label, .. } => label.as_ref(),
+            _ => "",
+        };
+        ctx.nested(PathSegment::Index(idx), |ctx| -> StringifyResult<()> {
+            this.indent(buf, styles, ctx, &end)?;
+            let row_open = options.labels.open(row_label, open);
+            if line.is_empty() {
+                let empty = format!("{}{}", row_open, close);
+                return Ok(options.theme.write(buf, Role::Container, &empty)?);
+            }
+            options.theme.write(buf, Role::Container, &row_open)?;
+            for (col, (cell, _)) in line.iter().enumerate() {
+                let padding = " ".repeat(widths[col] - display_width(cell));
+                if right[col] {
+                    buf.write_all(padding.as_bytes())?;
+                }
+                buf.write_all(cell.as_bytes())?;
+                let last = col + 1 == line.len();
+                ctx.flat(|ctx| this.write_separator(buf, styles, ctx, last))?;
+                if !right[col] && !last {
+                    buf.write_all(padding.as_bytes())?;
+                }
+            }
+            options.theme.write(buf, Role::Container, close)?;
+            Ok(())
+        })?;
+        this.write_separator(buf, styles, ctx, idx + 1 == rows.len())?;
+    }
+    this.indent(buf, styles, ctx, &end)?;
+    options.theme.write(buf, Role::Container, close)?;
+    Ok(())
 }
 
 impl<T> Stringify2 for Vec<T>
 where T: Stringify2 {
-    fn stringify<W>(&self, buf: &mut W, styles: &Styles) -> StringifyResult<()>
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        stringify_seq(self, buf, styles, ctx, "Vec", self.iter())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("Vec", styles, self.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("Vec", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> Stringify2 for VecDeque<T>
+where T: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        stringify_seq(self, buf, styles, ctx, "VecDeque", self.iter())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("VecDeque", styles, self.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("VecDeque", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> Stringify2 for LinkedList<T>
+where T: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        stringify_seq(self, buf, styles, ctx, "LinkedList", self.iter())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("LinkedList", styles, self.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("LinkedList", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Note that the elements of a `BinaryHeap` are written in arbitrary order.
+impl<T> Stringify2 for BinaryHeap<T>
+where T: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        stringify_seq(self, buf, styles, ctx, "BinaryHeap", self.iter())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("BinaryHeap", styles, self.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("BinaryHeap", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T, const N: usize> Stringify2 for [T; N]
+where T: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        stringify_seq(self, buf, styles, ctx, "Array", self.iter())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("Array", styles, self.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("Array", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> Stringify2 for [T]
+where T: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        stringify_seq(self, buf, styles, ctx, "Slice", self.iter())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        seq_node("Slice", styles, self.iter())
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("Slice", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Stringify a set-like container: `label` followed by the `items`
+/// between curly braces, one item per line with the `"end"` style.
+/// An empty set is written as `label {}`.
+/// The `labels` of the `Options` may replace or omit the `label`.
+fn stringify_set<'i, S, T, I, W>(this: &S,
+                                 buf: &mut W,
+                                 styles: &Styles,
+                                 ctx: &mut StringifyContext,
+                                 label: &str,
+                                 items: I) -> StringifyResult<()>
+where S: Stringify2 + ?Sized,
+      T: Stringify2 + 'i,
+      I: IntoIterator<Item = &'i T>,
+      I::IntoIter: ExactSizeIterator,
+      W: Write {
+    let labels = &styles.options().labels;
+    let delimiters = &styles.options().delimiters;
+    let (open, close) = &delimiters.set;
+    let mut items = items.into_iter().peekable();
+    if items.peek().is_none() {
+        let empty = format!("{}{}", open, close);
+        styles.options().theme.write(buf, Role::Container, &labels.open(label, &empty))?;
+        return Ok(());
+    }
+    let end: Style = styles.get_or_default(StyleKey::End);
+    this.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+    let opening = open_sized(styles, label, open, items.len(), ELEMENTS);
+    styles.options().theme.write(buf, Role::Container, &opening)?;
+    let max_elements = styles.options().max_elements.unwrap_or(usize::MAX);
+    for idx in 0 .. max_elements {
+        let item = match items.next() {
+            Some(item) => item,
+            None => break,
+        };
+        ctx.nested(PathSegment::Index(idx), |ctx| {
+            this.indent(buf, styles, ctx, &end)?;
+            write_index(buf, styles, idx)?;
+            item.stringify_component(buf, styles, ctx)
+        })?;
+        this.write_separator(buf, styles, ctx, items.peek().is_none())?;
+    }
+    write_remainder(this, buf, styles, ctx, &end, items.count())?;
+    this.indent(buf, styles, ctx, &end)?;
+    styles.options().theme.write(buf, Role::Container, close)?;
+    Ok(())
+}
+
+impl<T> Stringify2 for HashSet<T>
+where T: Stringify2 + Eq + Hash {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        match &styles.options().order {
+            EntryOrder::Iteration =>
+                stringify_set(self, buf, styles, ctx, "HashSet", self.iter()),
+            order => {
+                let items = self.iter().map(|item| (item, ()));
+                let items = sort_entries(styles, order, items, |()| Ok(String::new()))?;
+                let items = items.into_iter().map(|(item, ())| item);
+                stringify_set(self, buf, styles, ctx, "HashSet", items)
+            },
+        }
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        let items = self.iter().map(|item| (item, ()));
+        let items = sort_entries(styles, &styles.options().order, items, |()| Ok(String::new()))?;
+        set_node("HashSet", styles, items.into_iter().map(|(item, ())| item))
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("HashSet", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> Stringify2 for BTreeSet<T>
+where T: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        match &styles.options().order {
+            order @ EntryOrder::Custom(_) => {
+                let items = self.iter().map(|item| (item, ()));
+                let items = sort_entries(styles, order, items, |()| Ok(String::new()))?;
+                let items = items.into_iter().map(|(item, ())| item);
+                stringify_set(self, buf, styles, ctx, "BTreeSet", items)
+            },
+            _ => stringify_set(self, buf, styles, ctx, "BTreeSet", self.iter()),
+        }
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        match &styles.options().order {
+            order @ EntryOrder::Custom(_) => {
+                let items = self.iter().map(|item| (item, ()));
+                let items = sort_entries(styles, order, items, |()| Ok(String::new()))?;
+                set_node("BTreeSet", styles, items.into_iter().map(|(item, ())| item))
+            },
+            _ => set_node("BTreeSet", styles, self.iter()),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        seq_hint("BTreeSet", self.iter())
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> Stringify2 for Option<T>
+where T: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let theme = styles.options().theme;
+        let start = styles.get_or_default(StyleKey::Start);
+        match self {
+            Some(value) => {
+                self.indent(buf, styles, ctx, &start)?;
+                theme.write(buf, Role::Container, "Some(")?;
+                ctx.inline(PathSegment::Index(0), |ctx| value.stringify_component(buf, styles, ctx))?;
+                theme.write(buf, Role::Container, ")")?;
+            },
+            None => match styles.options().none {
+                NoneStyle::Print => {
+                    self.indent(buf, styles, ctx, &start)?;
+                    theme.write(buf, Role::Container, "None")?;
+                },
+                NoneStyle::Omit => {},
+            },
+        }
+        Ok(())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        match self {
+            Some(value) => tuple_node("Some", vec![Node::of(value, styles)?]),
+            None => Ok(Node::Null),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            Some(value) => "Some()".len() + value.size_hint(),
+            None => "None".len(),
+        }
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<T, E> Stringify2 for Result<T, E>
+where T: Stringify2,
+      E: Stringify2 {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let theme = styles.options().theme;
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+        match self {
+            Ok(value) => {
+                theme.write(buf, Role::Container, "Ok(")?;
+                ctx.inline(PathSegment::Index(0), |ctx| value.stringify_component(buf, styles, ctx))?;
+            },
+            Err(err) => {
+                theme.write(buf, Role::Container, "Err(")?;
+                ctx.inline(PathSegment::Index(0), |ctx| err.stringify_component(buf, styles, ctx))?;
+            },
+        }
+        theme.write(buf, Role::Container, ")")?;
+        Ok(())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        match self {
+            Ok(value) => tuple_node("Ok", vec![Node::of(value, styles)?]),
+            Err(err) => tuple_node("Err", vec![Node::of(err, styles)?]),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            Ok(value) => "Ok()".len() + value.size_hint(),
+            Err(err) => "Err()".len() + err.size_hint(),
+        }
+    }
+}
+
+macro_rules! impl_stringify2_for_tuples {
+    ($( ( $($name:ident $idx:tt),+ ) )+) => {
+        $(
+            impl<$($name),+> Stringify2 for ($($name,)+)
+            where $($name: Stringify2),+ {
+                fn stringify<W>(&self,
+                                buf: &mut W,
+                                styles: &Styles,
+                                ctx: &mut StringifyContext) -> StringifyResult<()>
+                where W: Write {
+                    let end: Style = styles.get_or_default(StyleKey::End);
+                    let delimiters = &styles.options().delimiters;
+                    let (open, close) = &delimiters.tuple;
+                    self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+                    styles.options().theme.write(buf, Role::Container, open)?;
+                    let last = [$($idx),+].len() - 1;
+                    $(
+                        ctx.nested(PathSegment::Index($idx), |ctx| {
+                            self.indent(buf, styles, ctx, &end)?;
+                            self.$idx.stringify_component(buf, styles, ctx)
+                        })?;
+                        self.write_separator(buf, styles, ctx, $idx == last)?;
+                    )+
+                    self.indent(buf, styles, ctx, &end)?;
+                    styles.options().theme.write(buf, Role::Container, close)?;
+                    Ok(())
+                }
+
+                fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+                    tuple_node("", vec![$(Node::of(&self.$idx, styles)?),+])
+                }
+
+                fn size_hint(&self) -> usize {
+                    2 $(+ self.$idx.size_hint() + LINE_HINT)+
+                }
+            }
+        )+
+    };
+}
+
+impl_stringify2_for_tuples! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+macro_rules! impl_stringify2_for_pointers {
+    ($($ptr:ty => $stringify:ident),*) => {
+        $(
+            impl<T> Stringify2 for $ptr
+            where T: Stringify2 + ?Sized {
+                fn stringify<W>(&self,
+                                buf: &mut W,
+                                styles: &Styles,
+                                ctx: &mut StringifyContext) -> StringifyResult<()>
+                where W: Write {
+                    $stringify(&**self, buf, styles, ctx)
+                }
+
+                fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+                    Node::of(&**self, styles)
+                }
+
+                fn size_hint(&self) -> usize {
+                    (**self).size_hint()
+                }
+
+                fn is_unset(&self) -> bool {
+                    (**self).is_unset()
+                }
+
+                fn stringify_flattened<W>(&self,
+                                          buf: &mut W,
+                                          styles: &Styles,
+                                          ctx: &mut StringifyContext,
+                                          name: &str,
+                                          last: bool) -> StringifyResult<()>
+                where W: Write {
+                    (**self).stringify_flattened(buf, styles, ctx, name, last)
+                }
+            }
+        )*
+    };
+}
+
+impl_stringify2_for_pointers!(
+    &T => stringify_owned, &mut T => stringify_owned, Box<T> => stringify_owned,
+    Rc<T> => stringify_shared, Arc<T> => stringify_shared
+);
+
+/// Stringify the `value` that a pointer owns or borrows.
+fn stringify_owned<T, W>(value: &T,
+                         buf: &mut W,
+                         styles: &Styles,
+                         ctx: &mut StringifyContext) -> StringifyResult<()>
+where T: Stringify2 + ?Sized,
+      W: Write {
+    value.stringify(buf, styles, ctx)
+}
+
+/// Stringify the `value` that an `Rc` or `Arc` shares. With `Options::dedup`,
+/// a value that was written before at another path is written as a reference
+/// to that path, e.g. `<same as .services[2].config>`.
+fn stringify_shared<T, W>(value: &T,
+                          buf: &mut W,
+                          styles: &Styles,
+                          ctx: &mut StringifyContext) -> StringifyResult<()>
+where T: Stringify2 + ?Sized,
+      W: Write {
+    let options = styles.options();
+    if options.dedup {
+        let address = value as *const T as *const () as usize;
+        if let Some(path) = ctx.shared_path(address) {
+            let path = if path.is_empty() { "the root value" } else { &path };
+            options.theme.write(buf, Role::Container, &format!("<same as {}>", path))?;
+            return Ok(());
+        }
+    }
+    value.stringify(buf, styles, ctx)
+}
+
+impl<T> Stringify2 for Cow<'_, T>
+where T: Stringify2 + ToOwned + ?Sized {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        (**self).stringify(buf, styles, ctx)
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        Node::of(&**self, styles)
+    }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
+
+    fn is_unset(&self) -> bool {
+        (**self).is_unset()
+    }
+}
+
+impl<T> Stringify2 for Cell<T>
+where T: Stringify2 + Copy {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        self.get().stringify(buf, styles, ctx)
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        Node::of(&self.get(), styles)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.get().size_hint()
+    }
+}
+
+/// Writes `<borrowed>` if the value is currently mutably borrowed.
+impl<T> Stringify2 for RefCell<T>
+where T: Stringify2 + ?Sized {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        match self.try_borrow() {
+            Ok(value) => value.stringify(buf, styles, ctx),
+            Err(_) => {
+                styles.options().theme.write(buf, Role::Error, "<borrowed>")?;
+                Ok(())
+            },
+        }
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        match self.try_borrow() {
+            Ok(value) => Node::of(&*value, styles),
+            Err(_) => Ok(Node::Text("<borrowed>".to_string())),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        self.try_borrow().map_or(0, |value| value.size_hint())
+    }
+}
+
+/// Writes `<locked>` if the lock is currently held elsewhere.
+/// A poisoned lock does not prevent the value from being written.
+impl<T> Stringify2 for Mutex<T>
+where T: Stringify2 + ?Sized {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
     where W: Write {
-        if self.is_empty() {
-            buf.write_all("Vec []".as_bytes())?;
+        match self.try_lock() {
+            Ok(guard) => guard.stringify(buf, styles, ctx),
+            Err(TryLockError::Poisoned(err)) => err.get_ref().stringify(buf, styles, ctx),
+            Err(TryLockError::WouldBlock) => {
+                styles.options().theme.write(buf, Role::Error, "<locked>")?;
+                Ok(())
+            },
+        }
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        match self.try_lock() {
+            Ok(guard) => Node::of(&*guard, styles),
+            Err(TryLockError::Poisoned(err)) => Node::of(&**err.get_ref(), styles),
+            Err(TryLockError::WouldBlock) => Ok(Node::Text("<locked>".to_string())),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        self.try_lock().map_or(0, |guard| guard.size_hint())
+    }
+}
+
+/// Writes `<locked>` if the lock is currently held for writing.
+/// A poisoned lock does not prevent the value from being written.
+impl<T> Stringify2 for RwLock<T>
+where T: Stringify2 + ?Sized {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        match self.try_read() {
+            Ok(guard) => guard.stringify(buf, styles, ctx),
+            Err(TryLockError::Poisoned(err)) => err.get_ref().stringify(buf, styles, ctx),
+            Err(TryLockError::WouldBlock) => {
+                styles.options().theme.write(buf, Role::Error, "<locked>")?;
+                Ok(())
+            },
+        }
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        match self.try_read() {
+            Ok(guard) => Node::of(&*guard, styles),
+            Err(TryLockError::Poisoned(err)) => Node::of(&**err.get_ref(), styles),
+            Err(TryLockError::WouldBlock) => Ok(Node::Text("<locked>".to_string())),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        self.try_read().map_or(0, |guard| guard.size_hint())
+    }
+}
+
+impl Stringify2 for bool {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    _: &Styles,
+                    _: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        write!(buf, "{}", self)?;
+        Ok(())
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(Node::Bool(*self))
+    }
+
+    fn size_hint(&self) -> usize {
+        "false".len()
+    }
+
+    fn is_unset(&self) -> bool {
+        !*self
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/// Write `text` according to the `Quoting` and `Multiline` policies
+/// in `styles`, using `quote` as the quotation mark.
+fn write_text<W>(buf: &mut W,
+                 styles: &Styles,
+                 ctx: &StringifyContext,
+                 text: &str,
+                 quote: char) -> StringifyResult<()>
+where W: Write {
+    let theme = styles.options().theme;
+    theme.begin(buf, Role::String)?;
+    write_unstyled_text(buf, styles, ctx, text, quote)?;
+    theme.end(buf, Role::String)?;
+    Ok(())
+}
+
+fn write_unstyled_text<W>(buf: &mut W,
+                          styles: &Styles,
+                          ctx: &StringifyContext,
+                          text: &str,
+                          quote: char) -> StringifyResult<()>
+where W: Write {
+    if styles.options().quoting == Quoting::Raw {
+        let multiline = styles.options().multiline;
+        if multiline == Multiline::Verbatim || !text.contains('\n') {
+            buf.write_all(text.as_bytes())?;
             return Ok(());
         }
-        let end: Style = styles.get("end")?;
-        self.indent(buf, styles.get("start")?)?;
-        buf.write_all("Vec [".as_bytes())?;
-        for item in self.iter() {
-            self.indent(buf, end + 1)?;
-            item.stringify(buf, styles)?;
-            buf.write_all(",".as_bytes())?;
+        let flat = styles.options().layout == Layout::Compact || ctx.is_flat();
+        let line_style = styles.get_or_default(StyleKey::End).with_newline(Newline::Add);
+        for (idx, line) in text.split('\n').enumerate() {
+            let margin = multiline == Multiline::Margin;
+            if idx > 0 && flat {
+                buf.write_all(" ".as_bytes())?;
+            } else if idx > 0 || (margin && ctx.line_started_cell().get()) {
+                text.indent(buf, styles, ctx, &line_style)?;
+            }
+            if margin {
+                buf.write_all("| ".as_bytes())?;
+            }
+            buf.write_all(line.as_bytes())?;
         }
-        self.indent(buf, end)?;
-        buf.write_all("]".as_bytes())?;
+        return Ok(());
+    }
+    write!(buf, "{}", quote)?;
+    for c in text.chars() {
+        match c {
+            '\n' => buf.write_all("\\n".as_bytes())?,
+            '\r' => buf.write_all("\\r".as_bytes())?,
+            '\t' => buf.write_all("\\t".as_bytes())?,
+            '\\' => buf.write_all("\\\\".as_bytes())?,
+            c if c == quote => write!(buf, "\\{}", c)?,
+            c if c.is_control() => write!(buf, "\\u{{{:x}}}", c as u32)?,
+            c => write!(buf, "{}", c)?,
+        }
+    }
+    write!(buf, "{}", quote)?;
+    Ok(())
+}
+
+impl Stringify2 for String {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        write_text(buf, styles, ctx, self, '"')
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(Node::String(self.clone()))
+    }
+
+    fn size_hint(&self) -> usize {
+        self.len() + 2
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+impl Stringify2 for str {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        write_text(buf, styles, ctx, self, '"')
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(Node::String(self.to_string()))
+    }
+
+    fn size_hint(&self) -> usize {
+        self.len() + 2
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Write a float like its `Display` impl does, i.e. as the shortest number
+/// of decimal digits that reads back as the same float, without exponent.
+#[cfg(not(feature = "ryu"))]
+fn write_float<F, W>(buf: &mut W, value: F) -> StringifyResult<()>
+where F: fmt::Display,
+      W: Write {
+    write!(buf, "{}", value)?;
+    Ok(())
+}
+
+/// Write a float like its `Display` impl does, i.e. as the shortest number
+/// of decimal digits that reads back as the same float, without exponent.
+/// The digits are found by `ryu`, whose exponent notation is expanded,
+/// e.g. `1e-7` is written as `0.0000001` and `1.0` as `1`. Rarely, `ryu`
+/// picks a different last digit than `Display` among the shortest ones
+/// that read back as the same float.
+#[cfg(feature = "ryu")]
+fn write_float<F, W>(buf: &mut W, value: F) -> StringifyResult<()>
+where F: ryu::Float,
+      W: Write {
+    const ZEROS: &[u8] = b"0000000000000000000000000000000000000000000000000000000000000000";
+    fn write_zeros<W: Write>(buf: &mut W, mut count: usize) -> StringifyResult<()> {
+        while count > 0 {
+            let n = count.min(ZEROS.len());
+            buf.write_all(&ZEROS[.. n])?;
+            count -= n;
+        }
+        Ok(())
+    }
+    let mut buffer = ryu::Buffer::new();
+    let text = buffer.format(value);
+    let (mantissa, exponent) = match text.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<isize>().unwrap_or(0)),
+        None if text.ends_with(".0") => (&text[.. text.len() - 2], 0),
+        None => (text, 0),
+    };
+    if exponent == 0 {
+        buf.write_all(mantissa.as_bytes())?;
+        return Ok(());
+    }
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = int.len() + frac.len();
+    let point = int.len() as isize + exponent;
+    buf.write_all(sign.as_bytes())?;
+    if point <= 0 {
+        buf.write_all(b"0.")?;
+        write_zeros(buf, point.unsigned_abs())?;
+        buf.write_all(int.as_bytes())?;
+        buf.write_all(frac.as_bytes())?;
+    } else if point as usize >= digits {
+        buf.write_all(int.as_bytes())?;
+        buf.write_all(frac.as_bytes())?;
+        write_zeros(buf, point as usize - digits)?;
+    } else {
+        let point = point as usize - int.len();
+        buf.write_all(int.as_bytes())?;
+        buf.write_all(&frac.as_bytes()[.. point])?;
+        buf.write_all(b".")?;
+        buf.write_all(&frac.as_bytes()[point ..])?;
+    }
+    Ok(())
+}
+
+/// Write an integer in the radix and with the digit grouping
+/// specified by the `Options` in `styles`.
+fn write_integer<I, W>(buf: &mut W, styles: &Styles, value: I) -> StringifyResult<()>
+where I: fmt::Display + fmt::LowerHex + fmt::Octal + fmt::Binary,
+      W: Write {
+    let theme = styles.options().theme;
+    theme.begin(buf, Role::Number)?;
+    write_unstyled_integer(buf, styles, value)?;
+    theme.end(buf, Role::Number)?;
+    Ok(())
+}
+
+fn write_unstyled_integer<I, W>(buf: &mut W, styles: &Styles, value: I) -> StringifyResult<()>
+where I: fmt::Display + fmt::LowerHex + fmt::Octal + fmt::Binary,
+      W: Write {
+    let number = styles.options().number;
+    let (formatted, group_size) = match number.radix {
+        Radix::Decimal => (format!("{}", value), 3),
+        Radix::Hexadecimal => (format!("{:#x}", value), 4),
+        Radix::Octal => (format!("{:#o}", value), 3),
+        Radix::Binary => (format!("{:#b}", value), 4),
+    };
+    match number.grouping {
+        Grouping::Off => buf.write_all(formatted.as_bytes())?,
+        Grouping::Separator(sep) => {
+            let sign_len = if formatted.starts_with('-') { 1 } else { 0 };
+            let prefix_len = match number.radix {
+                Radix::Decimal => sign_len,
+                _ => sign_len + 2,
+            };
+            let (prefix, digits) = formatted.split_at(prefix_len);
+            buf.write_all(prefix.as_bytes())?;
+            for (idx, digit) in digits.chars().enumerate() {
+                if idx > 0 && (digits.len() - idx) % group_size == 0 {
+                    write!(buf, "{}", sep)?;
+                }
+                write!(buf, "{}", digit)?;
+            }
+        },
+    }
+    Ok(())
+}
+
+macro_rules! impl_stringify2_for_integers {
+    ($($int:ty),*) => {
+        $(
+            impl Stringify2 for $int {
+                fn stringify<W>(&self,
+                                buf: &mut W,
+                                styles: &Styles,
+                                _: &mut StringifyContext) -> StringifyResult<()>
+                where W: Write {
+                    write_integer(buf, styles, *self)
+                }
+
+                fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+                    Ok(Node::Number(self.to_string()))
+                }
+
+                fn size_hint(&self) -> usize {
+                    // The number of decimal digits, plus one for a sign.
+                    (self.abs_diff(0) as u128).checked_ilog10().map_or(1, |log| log as usize + 2)
+                }
+
+                fn is_unset(&self) -> bool {
+                    *self == 0
+                }
+
+                fn as_any(&self) -> Option<&dyn Any> {
+                    Some(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_stringify2_for_integers!(
+    usize, u8, u16, u32, u64, u128,
+    isize, i8, i16, i32, i64, i128
+);
+
+macro_rules! impl_stringify2_for_floats {
+    ($($float:ty),*) => {
+        $(
+            impl Stringify2 for $float {
+                fn stringify<W>(&self,
+                                buf: &mut W,
+                                styles: &Styles,
+                                _: &mut StringifyContext) -> StringifyResult<()>
+                where W: Write {
+                    let number = styles.options().number;
+                    let theme = styles.options().theme;
+                    theme.begin(buf, Role::Number)?;
+                    match (number.notation, number.precision) {
+                        (Notation::Fixed, None) if styles.options().canonical => write!(buf, "{}", self)?,
+                        (Notation::Fixed, None) => write_float(buf, *self)?,
+                        (Notation::Fixed, Some(p)) => write!(buf, "{:.*}", p, self)?,
+                        (Notation::Scientific, None) => write!(buf, "{:e}", self)?,
+                        (Notation::Scientific, Some(p)) => write!(buf, "{:.*e}", p, self)?,
+                    }
+                    theme.end(buf, Role::Number)?;
+                    Ok(())
+                }
+
+                fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+                    Ok(Node::Number(self.to_string()))
+                }
+
+                fn size_hint(&self) -> usize {
+                    FLOAT_HINT
+                }
+
+                fn is_unset(&self) -> bool {
+                    *self == 0.0
+                }
+
+                fn as_any(&self) -> Option<&dyn Any> {
+                    Some(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_stringify2_for_floats!(f32, f64);
+
+impl Stringify2 for char {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        write_text(buf, styles, ctx, self.encode_utf8(&mut [0; 4]), '\'')
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(Node::Char(*self))
+    }
+
+    fn size_hint(&self) -> usize {
+        self.len_utf8() + 2
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+impl Stringify2 for Style {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let theme = styles.options().theme;
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::Start))?;
+        theme.write(buf, Role::Container, "Style {")?;
+        self.stringify_field(buf, styles, ctx, "newline", &self.newline)?;
+        self.write_separator(buf, styles, ctx, false)?;
+        self.stringify_field(buf, styles, ctx, "indent_level", &self.indent_level)?;
+        self.write_separator(buf, styles, ctx, true)?;
+        self.indent(buf, styles, ctx, &styles.get_or_default(StyleKey::End))?;
+        theme.write(buf, Role::Container, "}")?;
+        Ok(())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        Ok(Node::Struct {
+            label: Cow::Borrowed("Style"),
+            fields: vec![
+                (Cow::Borrowed("newline"), Node::of(&self.newline, styles)?),
+                (Cow::Borrowed("indent_level"), Node::of(&self.indent_level, styles)?),
+            ],
+        })
+    }
+}
+
+impl Stringify2 for Newline {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    _: &Styles,
+                    _: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        write!(buf, "Newline::{:?}", self)?;
         Ok(())
     }
 }
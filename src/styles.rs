@@ -1,40 +1,322 @@
 use crate::error::{StringifyError, StringifyResult};
 use crate::newline::Newline;
+use crate::context::PathSegment;
+use crate::options::Options;
+use crate::selector::Selector;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::iter::FromIterator;
 use std::ops;
+use std::sync::OnceLock;
 
+static DEFAULT_STYLE: OnceLock<Style> = OnceLock::new();
+
+/// Set the process-wide `Style::default()`, e.g. to switch to tabs or 2-space
+/// indentation everywhere. The default `Style`s of `Styles::get_or_default`
+/// use its indentation unit. This can only be done once; if the default
+/// has already been set, `style` is returned as an error.
+pub fn set_default_style(style: Style) -> Result<(), Style> {
+    DEFAULT_STYLE.set(style)
+}
+
+/// Create `Styles` from `key => style` pairs, where each key is a
+/// `&'static str` or a `StyleKey`.
+/// The keys that are required can be declared up front, in which case the
+/// keys must be string literals, and a missing key is a compile error:
+/// ```text
+/// styles!(require start, end;
+///     "start" => Style::standard(Newline::Omit, 0),
+///     "end" => Style::standard(Newline::Add, 0)
+/// )
+/// ```
 #[macro_export]
 macro_rules! styles {
+    (
+        require $($required:ident),+ ;
+        $($key:literal => $value:expr),*
+    ) => {{
+        const KEYS: &[&str] = &[$($key),*];
+        $(
+            const _: () = assert!(
+                $crate::__contains_key(KEYS, stringify!($required)),
+                concat!("styles! requires a \"", stringify!($required), "\" style")
+            );
+        )+
+        $crate::styles! { $($key => $value),* }
+    }};
     (
         $($key:expr => $value:expr),*
     ) => {{
         use std::collections::BTreeMap;
         #[allow(unused_mut)] let mut btmap = BTreeMap::new();
         $(
-            btmap.insert($key, $value);
+            btmap.insert($crate::StyleKey::from($key).name(), $value);
         )*
-            $crate::styles::Styles::new(btmap)
+            $crate::Styles::new(btmap)
     }};
 }
 
-pub struct Styles(BTreeMap<&'static str, Style>);
+/// Used by `styles!` to check for required keys at compile time.
+#[doc(hidden)]
+pub const fn __contains_key(keys: &[&str], key: &str) -> bool {
+    let mut idx = 0;
+    while idx < keys.len() {
+        if str_eq(keys[idx], key) {
+            return true;
+        }
+        idx += 1;
+    }
+    false
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut idx = 0;
+    while idx < a.len() {
+        if a[idx] != b[idx] {
+            return false;
+        }
+        idx += 1;
+    }
+    true
+}
+
+/// The name of a `Style` in `Styles`. Using the variants rather than
+/// string literals catches typos such as `"strat"` at compile time.
+/// Any `&'static str` converts to a `StyleKey`, so both can be used
+/// wherever a `StyleKey` is expected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StyleKey {
+    /// `"start"`, used before a container label.
+    Start,
+    /// `"end"`, used before each element of a container and before
+    /// its closing delimiter.
+    End,
+    /// `"key"`, used before the key of a map entry instead of `"end"`.
+    Key,
+    /// `"value"`, used before the value of a map entry, if present.
+    Value,
+    /// `"name"`, used before the name of a struct field.
+    Name,
+    /// `"child"`, which is reserved for hand-written impls, e.g. to use
+    /// before a component of a value. The impls in this crate and the
+    /// derive don't use it.
+    Child,
+    /// Any other name.
+    Other(&'static str),
+}
+
+impl StyleKey {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StyleKey::Start => "start",
+            StyleKey::End => "end",
+            StyleKey::Key => "key",
+            StyleKey::Value => "value",
+            StyleKey::Name => "name",
+            StyleKey::Child => "child",
+            StyleKey::Other(name) => name,
+        }
+    }
+}
+
+impl From<&'static str> for StyleKey {
+    fn from(name: &'static str) -> Self {
+        match name {
+            "start" => StyleKey::Start,
+            "end" => StyleKey::End,
+            "key" => StyleKey::Key,
+            "value" => StyleKey::Value,
+            "name" => StyleKey::Name,
+            "child" => StyleKey::Child,
+            name => StyleKey::Other(name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Styles {
+    map: BTreeMap<&'static str, Style>,
+    options: Options,
+    /// Consulted by `get` for names that are missing from `map`.
+    fallback: Option<Box<Styles>>,
+    /// Overrides for the values whose paths match a `Selector`.
+    selectors: Vec<(Selector, Styles)>,
+}
 
 impl Styles {
     pub fn new(map: BTreeMap<&'static str, Style>) -> Self {
-        Styles(map)
+        Styles { map, options: Options::default(), fallback: None, selectors: vec![] }
+    }
+
+    /// Replace the `Options` carried by `self`.
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Add the `Style` for `key`, replacing any existing one.
+    pub fn with<K>(mut self, key: K, style: Style) -> Self
+    where K: Into<StyleKey> {
+        self.map.insert(key.into().name(), style);
+        self
     }
 
-    pub fn get(&self, name: &'static str) -> StringifyResult<Style> {
-        match self.0.get(name) {
-            Some(style) => Ok(*style),
-            None => Err(StringifyError::StyleNotFound { name })?,
+    /// Add all `Style`s and selectors of `other` to `self`, replacing the
+    /// `Style`s with the same names. The `Options` and the fallback of
+    /// `self` are kept.
+    pub fn merge(mut self, other: Styles) -> Self {
+        self.map.extend(other.map);
+        self.selectors.extend(other.selectors);
+        self
+    }
+
+    /// Stringify the values whose paths match `selector` with the `Style`s
+    /// of `self` overridden by those in `overrides` (see `Styles::scoped`),
+    /// e.g. to write one noisy subtree more compactly than the rest:
+    /// ```text
+    /// styles.with_selector("servers[*].port".parse()?, styles! { StyleKey::End => Style::INLINE })
+    /// ```
+    /// If several selectors match a value, the one added last is used.
+    pub fn with_selector(mut self, selector: Selector, overrides: Styles) -> Self {
+        self.selectors.push((selector, overrides));
+        self
+    }
+
+    /// The overrides for the value at `path`, if any.
+    pub(crate) fn select(&self, path: &[PathSegment]) -> Option<&Styles> {
+        self.selectors.iter().rev()
+            .find(|(selector, _)| selector.matches(path))
+            .map(|(_, overrides)| overrides)
+    }
+
+    /// Run `f` with the `Style`s of `self` overridden by those in `overrides`,
+    /// e.g. to stringify a subtree in compact mode from an impl of `Stringify2`:
+    /// ```text
+    /// let compact = styles! { StyleKey::End => Style::INLINE };
+    /// styles.scoped(compact, |styles| value.stringify(buf, styles, ctx))?;
+    /// ```
+    /// The overrides only apply within `f`, and `self` is left unchanged.
+    pub fn scoped<F, R>(&self, overrides: Styles, f: F) -> R
+    where F: FnOnce(&Styles) -> R {
+        let scoped = self.clone().merge(overrides);
+        f(&scoped)
+    }
+
+    /// Look up the names that are missing from `self` in `fallback`, e.g.
+    /// so that library code can supply defaults that callers only partially
+    /// override. Fallbacks can be chained, and the `Options` of a fallback
+    /// are not used.
+    pub fn with_fallback(mut self, fallback: Styles) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Add the `Style` for `key`, and return the `Style` it replaces.
+    pub fn insert<K>(&mut self, key: K, style: Style) -> Option<Style>
+    where K: Into<StyleKey> {
+        self.map.insert(key.into().name(), style)
+    }
+
+    /// Remove the `Style` for `key` from `self`, but not from its fallback.
+    pub fn remove<K>(&mut self, key: K) -> Option<Style>
+    where K: Into<StyleKey> {
+        self.map.remove(key.into().name())
+    }
+
+    /// Iterate over the `Style`s of `self` in the order of their names.
+    /// The `Style`s of the fallback are not included.
+    pub fn iter(&self) -> impl Iterator<Item = (StyleKey, &Style)> + '_ {
+        self.map.iter().map(|(name, style)| (StyleKey::from(*name), style))
+    }
+
+    /// The number of `Style`s in `self`, not counting its fallback.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn get<K>(&self, key: K) -> StringifyResult<Style>
+    where K: Into<StyleKey> {
+        let name = key.into().name();
+        match (self.map.get(name), &self.fallback) {
+            (Some(style), _) => Ok(style.clone()),
+            (None, Some(fallback)) => fallback.get(name),
+            (None, None) => Err(StringifyError::StyleNotFound { name })?,
         }
     }
+
+    /// Like `get`, but return `default` if there is no `Style` for `key`.
+    pub fn get_or<K>(&self, key: K, default: Style) -> Style
+    where K: Into<StyleKey> {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Like `get`, but fall back to the default `Style` for `key`.
+    /// The keys that are used by the impls of `Stringify2` in this crate
+    /// have the following defaults, which write containers over multiple
+    /// lines with their elements and fields one level deeper:
+    /// - `StyleKey::Start`: `Newline::Omit`, level 0
+    /// - `StyleKey::Name`: `Newline::Add`, level 1
+    /// - `StyleKey::End`: `Newline::Add`, level 0
+    ///
+    /// Other keys default to `Style::default()`.
+    pub fn get_or_default<K>(&self, key: K) -> Style
+    where K: Into<StyleKey> {
+        let key = key.into();
+        self.get(key).unwrap_or_else(|_| Self::default_style(key))
+    }
+
+    fn default_style(key: StyleKey) -> Style {
+        let default = Style::default();
+        match key {
+            StyleKey::Start => Style { newline: Newline::Omit, indent_level: 0, ..default },
+            StyleKey::Name => Style { newline: Newline::Add, indent_level: 1, ..default },
+            StyleKey::End => Style { newline: Newline::Add, indent_level: 0, ..default },
+            _ => default,
+        }
+    }
+}
+
+
+impl Default for Styles {
+    fn default() -> Self {
+        Styles::new(BTreeMap::new())
+    }
 }
 
+impl<K> Extend<(K, Style)> for Styles
+where K: Into<StyleKey> {
+    fn extend<I>(&mut self, styles: I)
+    where I: IntoIterator<Item = (K, Style)> {
+        for (key, style) in styles {
+            self.insert(key, style);
+        }
+    }
+}
 
+impl<K> FromIterator<(K, Style)> for Styles
+where K: Into<StyleKey> {
+    fn from_iter<I>(styles: I) -> Self
+    where I: IntoIterator<Item = (K, Style)> {
+        let mut result = Styles::new(BTreeMap::new());
+        result.extend(styles);
+        result
+    }
+}
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Style {
     /// The policy for printing a newline.
     pub newline: Newline,
@@ -42,46 +324,116 @@ pub struct Style {
     /// The indentation level.
     pub indent_level: usize,
 
-    pub indent: &'static str,
+    /// The indentation unit, applied `indent_level` times.
+    /// This can be set at runtime e.g. to `"\t"` or 2 spaces.
+    pub indent: Cow<'static, str>,
 }
 
 impl Style {
     pub const INDENT: &'static str = "    "; // 4 spaces
 
-    pub fn standard(newline: Newline, indent_level: usize) -> Self {
+    /// Continue the current line.
+    pub const INLINE: Style = Style::standard(Newline::Omit, 0);
+
+    /// Start a new line, indented with 4 spaces per level.
+    pub const PRETTY: Style = Style::standard(Newline::Add, 0);
+
+    /// Start a new line, indented with 2 spaces per level.
+    pub const COMPACT: Style = Style {
+        newline: Newline::Add,
+        indent_level: 0,
+        indent: Cow::Borrowed("  "),
+    };
+
+    pub const fn standard(newline: Newline, indent_level: usize) -> Self {
         Self {
-            newline: newline,
-            indent_level: indent_level,
-            indent: Self::INDENT,
+            newline,
+            indent_level,
+            indent: Cow::Borrowed(Self::INDENT),
         }
     }
 
     #[inline(always)]
     pub fn unused() -> Self { Self::default() }
 
+    /// Build a `Style` starting from `Style::default()`, e.g.
+    /// `Style::builder().newline(Newline::Add).level(2).indent("\t").build()`.
+    pub fn builder() -> StyleBuilder {
+        StyleBuilder { style: Self::default() }
+    }
+
     pub fn with_newline(&self, newline: Newline) -> Self {
         Self {
-            newline: newline,
+            newline,
             indent_level: self.indent_level,
-            indent: self.indent,
+            indent: self.indent.clone(),
         }
     }
 
     pub fn with_indent_level(&self, indent_level: usize) -> Self {
         Self {
             newline: self.newline,
-            indent_level: indent_level,
-            indent: self.indent,
+            indent_level,
+            indent: self.indent.clone(),
         }
     }
+
+    pub fn with_indent<I>(&self, indent: I) -> Self
+    where I: Into<Cow<'static, str>> {
+        Self {
+            newline: self.newline,
+            indent_level: self.indent_level,
+            indent: indent.into(),
+        }
+    }
+
+    /// Dedent `self` by `levels`, stopping at level 0.
+    pub fn saturating_sub(&self, levels: usize) -> Self {
+        self.with_indent_level(self.indent_level.saturating_sub(levels))
+    }
+
+    /// Dedent `self` by `levels`, or return `None` if that would
+    /// dedent past level 0.
+    pub fn checked_sub(&self, levels: usize) -> Option<Self> {
+        self.indent_level.checked_sub(levels)
+            .map(|indent_level| self.with_indent_level(indent_level))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyleBuilder {
+    style: Style,
 }
 
+impl StyleBuilder {
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.style.newline = newline;
+        self
+    }
+
+    pub fn level(mut self, indent_level: usize) -> Self {
+        self.style.indent_level = indent_level;
+        self
+    }
+
+    pub fn indent<I>(mut self, indent: I) -> Self
+    where I: Into<Cow<'static, str>> {
+        self.style.indent = indent.into();
+        self
+    }
+
+    pub fn build(self) -> Style {
+        self.style
+    }
+}
+
+/// The default set with `set_default_style`, if any, and otherwise
+/// `Style::INLINE`.
 impl Default for Style {
     fn default() -> Self {
-        Style {
-            newline: Newline::Omit,
-            indent_level: 0,
-            indent: Self::INDENT,
+        match DEFAULT_STYLE.get() {
+            Some(style) => style.clone(),
+            None => Style::INLINE,
         }
     }
 }
@@ -110,26 +462,99 @@ impl ops::Add<Style> for Style {
     }
 }
 
+/// Subtraction saturates at level 0 rather than panicking.
 impl ops::Sub<usize> for Style {
     type Output = Style;
 
     fn sub(self, rhs: usize) -> Self::Output {
         Style {
             newline: self.newline,
-            indent_level: self.indent_level - rhs,
+            indent_level: self.indent_level.saturating_sub(rhs),
             indent: self.indent,
         }
     }
 }
 
+/// Subtraction saturates at level 0 rather than panicking.
 impl ops::Sub<Style> for Style {
     type Output = Style;
 
     fn sub(self, rhs: Style) -> Self::Output {
         Style {
             newline: self.newline,
-            indent_level: self.indent_level - rhs.indent_level,
+            indent_level: self.indent_level.saturating_sub(rhs.indent_level),
             indent: self.indent,
         }
     }
 }
+
+impl ops::AddAssign<usize> for Style {
+    fn add_assign(&mut self, rhs: usize) {
+        self.indent_level += rhs;
+    }
+}
+
+impl ops::AddAssign<Style> for Style {
+    fn add_assign(&mut self, rhs: Style) {
+        self.indent_level += rhs.indent_level;
+    }
+}
+
+/// Subtraction saturates at level 0 rather than panicking.
+impl ops::SubAssign<usize> for Style {
+    fn sub_assign(&mut self, rhs: usize) {
+        self.indent_level = self.indent_level.saturating_sub(rhs);
+    }
+}
+
+/// Subtraction saturates at level 0 rather than panicking.
+impl ops::SubAssign<Style> for Style {
+    fn sub_assign(&mut self, rhs: Style) {
+        self.indent_level = self.indent_level.saturating_sub(rhs.indent_level);
+    }
+}
+
+/// `Styles` are (de)serialized as a map from names to `Style`s.
+/// Their `Options`, fallback and selectors are not included.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Styles {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        serializer.collect_map(&self.map)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Styles {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let map: BTreeMap<String, Style> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(map.into_iter().map(|(name, style)| (intern(name), style)).collect())
+    }
+}
+
+/// Since the names in `Styles` are `&'static str`s, deserialized names
+/// that aren't well-known `StyleKey`s are leaked, but only once per name.
+#[cfg(feature = "serde")]
+fn intern(name: String) -> &'static str {
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let well_known = [
+        StyleKey::Start, StyleKey::End, StyleKey::Key,
+        StyleKey::Value, StyleKey::Name, StyleKey::Child,
+    ];
+    if let Some(key) = well_known.iter().find(|key| key.name() == name) {
+        return key.name();
+    }
+    let mut names = NAMES.lock().unwrap_or_else(|err| err.into_inner());
+    match names.get(name.as_str()) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(name.into_boxed_str());
+            names.insert(interned);
+            interned
+        },
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(any(feature = "toml", feature = "json"))]
+use crate::error::{StringifyError, StringifyResult};
+use crate::options::Options;
+use crate::styles::Styles;
+use crate::theme::Theme;
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "toml", feature = "json"))]
+use std::{fs, path::Path};
+
+/// A complete formatting profile that can be kept in an external file,
+/// so that it can be changed without recompiling, e.g. in TOML:
+/// ```toml
+/// [styles.start]
+/// newline = "Omit"
+/// indent_level = 0
+/// indent = "  "
+///
+/// [options]
+/// quoting = "Quoted"
+/// number = { grouping = { Separator = "_" } }
+/// theme = { number = "BrightYellow", string = "BrightGreen" }
+/// ```
+/// Options that are closures, such as `EntryOrder::Custom`, can't be
+/// part of a `Stylesheet`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stylesheet {
+    pub styles: Styles,
+    pub options: Options,
+}
+
+impl Stylesheet {
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> StringifyResult<Self> {
+        toml::from_str(text).map_err(invalid)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> StringifyResult<Self> {
+        serde_json::from_str(text).map_err(invalid)
+    }
+
+    /// Load a `Stylesheet` from a `.toml` or `.json` file.
+    #[cfg(any(feature = "toml", feature = "json"))]
+    pub fn from_path<P>(path: P) -> StringifyResult<Self>
+    where P: AsRef<Path> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&text),
+            #[cfg(feature = "json")]
+            Some("json") => Self::from_json(&text),
+            _ => Err(StringifyError::InvalidStylesheet {
+                message: format!("unsupported stylesheet format: {}", path.display()),
+            }),
+        }
+    }
+}
+
+impl From<Stylesheet> for Styles {
+    fn from(stylesheet: Stylesheet) -> Self {
+        stylesheet.styles.with_options(stylesheet.options)
+    }
+}
+
+impl Styles {
+    /// Load `Styles` and their `Options` from a `Stylesheet` file.
+    #[cfg(any(feature = "toml", feature = "json"))]
+    pub fn from_path<P>(path: P) -> StringifyResult<Self>
+    where P: AsRef<Path> {
+        Stylesheet::from_path(path).map(Styles::from)
+    }
+}
+
+impl Theme {
+    /// Parse a `Theme` from a TOML table of roles to colors, e.g.
+    /// `number = "BrightYellow"`. Missing roles are not colored.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> StringifyResult<Self> {
+        toml::from_str(text).map_err(invalid)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> StringifyResult<Self> {
+        serde_json::from_str(text).map_err(invalid)
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+fn invalid<E>(err: E) -> StringifyError
+where E: std::fmt::Display {
+    StringifyError::InvalidStylesheet { message: err.to_string() }
+}
@@ -0,0 +1,257 @@
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::layout::display_width;
+use crate::newline::Newline;
+use crate::node::{key_text, Compact, Node, Render};
+use crate::options::Layout;
+use crate::stringify::Stringify2;
+use crate::styles::{StyleKey, Styles};
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// The column names and rows of cells of a `Node`, for renderers that write
+/// it as a table. The items of a sequence or tuple each become a row, and
+/// any other node becomes a single row. Rows have a cell for each field of
+/// a struct or entry of a map, and columns are named after those fields and
+/// keys in the order in which they first appear. Rows of any other node
+/// have a single cell in the column `value`.
+struct Records {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Records {
+    fn of(node: &Node) -> io::Result<Self> {
+        let items = match node {
+            Node::Seq { items, .. } | Node::Set { items, .. } | Node::Tuple { items, .. } => items.as_slice(),
+            node => std::slice::from_ref(node),
+        };
+        let mut columns: Vec<String> = vec![];
+        let mut cells = vec![];
+        for item in items {
+            let mut row = vec![];
+            match item {
+                Node::Struct { fields, .. } => for (name, value) in fields {
+                    row.push((Cow::Borrowed(name.as_ref()), cell_text(value)?));
+                },
+                Node::Map { entries, .. } => for (key, value) in entries {
+                    row.push((key_text(key)?, cell_text(value)?));
+                },
+                item => row.push((Cow::Borrowed("value"), cell_text(item)?)),
+            }
+            for (name, _) in &row {
+                if !columns.iter().any(|column| column == name) {
+                    columns.push(name.to_string());
+                }
+            }
+            cells.push(row);
+        }
+        let rows = cells.into_iter().map(|row| {
+            columns.iter().map(|column| {
+                row.iter().find(|(name, _)| name == column)
+                    .map(|(_, text)| text.clone())
+                    .unwrap_or_default()
+            }).collect()
+        }).collect();
+        Ok(Records { columns, rows })
+    }
+}
+
+/// The text of the cell of `node`. Scalars are written as they are, and
+/// containers on a single line.
+fn cell_text(node: &Node) -> io::Result<String> {
+    Ok(match node {
+        Node::Null => String::new(),
+        Node::Bool(value) => value.to_string(),
+        Node::Number(text) | Node::String(text) | Node::Text(text) => text.clone(),
+        Node::Char(c) => c.to_string(),
+        Node::Tuple { items, .. } if items.len() == 1 => cell_text(&items[0])?,
+        node => Compact::default().render_to_string(node)
+            .map_err(|_| io::Error::other("cell can't be written"))?,
+    })
+}
+
+/// Renders a sequence of flat records, e.g. a `Vec` of structs, as CSV: a
+/// header row with the field names, followed by a row for each record.
+/// Cells that contain the `delimiter`, a quote or a line break are quoted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Csv {
+    pub delimiter: char,
+}
+
+impl Default for Csv {
+    fn default() -> Self {
+        Self { delimiter: ',' }
+    }
+}
+
+impl Csv {
+    fn write_row<W>(&self, buf: &mut W, cells: &[String]) -> io::Result<()>
+    where W: Write + ?Sized {
+        for (idx, cell) in cells.iter().enumerate() {
+            if idx > 0 {
+                write!(buf, "{}", self.delimiter)?;
+            }
+            if cell.contains([self.delimiter, '"', '\n', '\r']) {
+                write!(buf, "\"{}\"", cell.replace('"', "\"\""))?;
+            } else {
+                buf.write_all(cell.as_bytes())?;
+            }
+        }
+        buf.write_all(b"\n")
+    }
+}
+
+impl Render for Csv {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        let records = Records::of(node)?;
+        self.write_row(buf, &records.columns)?;
+        for row in &records.rows {
+            self.write_row(buf, row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders a sequence of flat records, e.g. a `Vec` of structs, as a plain
+/// text table: a header row with the field names, followed by a row for
+/// each record, with each column padded to the width of its widest cell.
+/// Widths are measured in terminal columns, so that e.g. CJK text lines up.
+/// `Table` values can also be stringified as part of other values with
+/// `Table::of`, which indents every line like the surrounding output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table {
+    /// The separator between the columns, if there are no `borders`.
+    pub separator: Cow<'static, str>,
+    /// Whether the table is drawn with box-drawing borders around
+    /// and between the cells, e.g. `│ name │ value │`.
+    pub borders: bool,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self { separator: Cow::Borrowed("  "), borders: false }
+    }
+}
+
+impl Table {
+    /// A table with box-drawing borders.
+    pub fn bordered() -> Self {
+        Self { borders: true, ..Self::default() }
+    }
+
+    /// A wrapper that stringifies `value` as a table laid out by `self`.
+    pub fn of<'t, T>(&'t self, value: &'t T) -> Tabulated<'t, T>
+    where T: Stringify2 + ?Sized {
+        Tabulated { table: self, value }
+    }
+
+    /// The lines of the table of `node`, without line endings.
+    fn lines(&self, node: &Node) -> io::Result<Vec<String>> {
+        let records = Records::of(node)?;
+        let widths: Vec<usize> = records.columns.iter().enumerate().map(|(idx, column)| {
+            records.rows.iter()
+                .map(|row| display_width(&row[idx]))
+                .fold(display_width(column), usize::max)
+        }).collect();
+        let mut lines = vec![];
+        if self.borders {
+            lines.push(self.rule(&widths, "┌", "┬", "┐"));
+        }
+        lines.push(self.row(&widths, &records.columns));
+        lines.push(match self.borders {
+            true => self.rule(&widths, "├", "┼", "┤"),
+            false => self.rule(&widths, "", &self.separator, ""),
+        });
+        lines.extend(records.rows.iter().map(|row| self.row(&widths, row)));
+        if self.borders {
+            lines.push(self.rule(&widths, "└", "┴", "┘"));
+        }
+        Ok(lines)
+    }
+
+    fn row(&self, widths: &[usize], cells: &[String]) -> String {
+        let (left, separator, right) = match self.borders {
+            true => ("│ ", " │ ", " │"),
+            false => ("", self.separator.as_ref(), ""),
+        };
+        let mut line = String::from(left);
+        for (idx, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            if idx > 0 {
+                line.push_str(separator);
+            }
+            line.push_str(cell);
+            line.push_str(&" ".repeat(width - display_width(cell)));
+        }
+        line.push_str(right);
+        match self.borders {
+            true => line,
+            false => line.trim_end().to_string(),
+        }
+    }
+
+    /// A horizontal rule under the cells of a row, which is drawn with
+    /// `─` between box-drawing borders, and with `-` otherwise. Without
+    /// borders, a separator such as ` | ` is continued as `-|-`.
+    fn rule(&self, widths: &[usize], left: &str, separator: &str, right: &str) -> String {
+        let (fill, padding) = match self.borders {
+            true => ("─", 2),
+            false => ("-", 0),
+        };
+        let cells: Vec<String> = widths.iter().map(|width| fill.repeat(width + padding)).collect();
+        let separator = match self.borders {
+            true => Cow::Borrowed(separator),
+            false => Cow::Owned(match separator.trim() {
+                "" => separator.to_string(),
+                _ => separator.replace(' ', "-"),
+            }),
+        };
+        format!("{}{}{}", left, cells.join(&separator), right)
+    }
+}
+
+impl Render for Table {
+    fn render(&self, node: &Node, buf: &mut dyn Write) -> StringifyResult<()> {
+        for line in self.lines(node)? {
+            writeln!(buf, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A value that is stringified as a table by `Table::of`. The table starts
+/// on a line of its own, and each of its lines is indented like the lines
+/// of the surrounding output. With `Layout::Compact`, or if the context is
+/// flat, the value is stringified as usual.
+pub struct Tabulated<'t, T: ?Sized> {
+    table: &'t Table,
+    value: &'t T,
+}
+
+impl<T> Stringify2 for Tabulated<'_, T>
+where T: Stringify2 + ?Sized {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        if styles.options().layout == Layout::Compact || ctx.is_flat() {
+            return self.value.stringify(buf, styles, ctx);
+        }
+        let mut line = styles.get_or_default(StyleKey::End);
+        line.newline = Newline::Add;
+        line.indent_level = 0;
+        let lines = self.table.lines(&Node::of(self.value, styles)?)?;
+        for (idx, text) in lines.iter().enumerate() {
+            if idx > 0 || ctx.column() > 0 {
+                self.indent(buf, styles, ctx, &line)?;
+            }
+            buf.write_all(text.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn to_node(&self, styles: &Styles) -> StringifyResult<Node> {
+        Node::of(self.value, styles)
+    }
+}
@@ -0,0 +1,135 @@
+//! Helpers for testing with stringified values as golden files.
+//!
+//! `assert_stringify_snapshot!(value)` stringifies `value` with the default
+//! `Stringifier`, and compares the result with a snapshot file under the
+//! `tests/snapshots/` directory of the crate being tested. Snapshots that
+//! don't exist yet are written, so that they can be reviewed and committed.
+//! When the `STRINGIFY_UPDATE_SNAPSHOTS` environment variable is set to a
+//! non-empty value, snapshots that differ are overwritten rather than
+//! failing the assertion:
+//! ```text
+//! STRINGIFY_UPDATE_SNAPSHOTS=1 cargo test
+//! ```
+
+use crate::stringifier::Stringifier;
+use crate::stringify::Stringify2;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The environment variable that turns on the update mode.
+pub const UPDATE_VAR: &str = "STRINGIFY_UPDATE_SNAPSHOTS";
+
+/// Assert that the stringification of a value matches its snapshot (see
+/// the `testing` module). Snapshots are named after the test function
+/// they are taken in, followed by a counter if there are several, or
+/// after an explicit name:
+/// ```text
+/// assert_stringify_snapshot!(config);
+/// assert_stringify_snapshot!("default_config", config);
+/// ```
+#[macro_export]
+macro_rules! assert_stringify_snapshot {
+    ($value:expr $(,)?) => {{
+        fn __here() {}
+        let name = $crate::testing::__function_name(__here);
+        $crate::testing::__assert_snapshot(&$value, env!("CARGO_MANIFEST_DIR"), &name, true)
+    }};
+    ($name:expr, $value:expr $(,)?) => {
+        $crate::testing::__assert_snapshot(&$value, env!("CARGO_MANIFEST_DIR"), $name, false)
+    };
+}
+
+/// The path of the snapshot file called `name` of the crate in `manifest_dir`.
+pub fn snapshot_path(manifest_dir: &str, name: &str) -> PathBuf {
+    Path::new(manifest_dir).join("tests").join("snapshots").join(format!("{}.snap", name))
+}
+
+/// The path of the function `f`, e.g. `my_crate__tests__parses_config`,
+/// which is derived from the type name of a function item declared in it.
+#[doc(hidden)]
+pub fn __function_name<F>(_: F) -> String {
+    let name = std::any::type_name::<F>();
+    let name = name.strip_suffix("::__here").unwrap_or(name);
+    name.replace("::{{closure}}", "").replace("::", "__")
+}
+
+thread_local! {
+    /// The number of unnamed snapshots taken per test function so far.
+    /// Tests run on threads of their own, so this counts per test.
+    static COUNTERS: RefCell<HashMap<String, usize>> = RefCell::new(HashMap::new());
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn __assert_snapshot<T>(value: &T, manifest_dir: &str, name: &str, numbered: bool)
+where T: Stringify2 + ?Sized {
+    let name = match numbered {
+        false => name.to_string(),
+        true => COUNTERS.with(|counters| {
+            let mut counters = counters.borrow_mut();
+            let count = counters.entry(name.to_string()).or_insert(0);
+            *count += 1;
+            match *count {
+                1 => name.to_string(),
+                count => format!("{}-{}", name, count),
+            }
+        }),
+    };
+    let actual = match Stringifier::default().to_string(value) {
+        Ok(actual) => actual + "\n",
+        Err(_) => panic!("snapshot `{}`: the value can't be stringified", name),
+    };
+    let path = snapshot_path(manifest_dir, &name);
+    let update = env::var_os(UPDATE_VAR).is_some_and(|v| !v.is_empty());
+    match fs::read_to_string(&path) {
+        Ok(expected) if expected == actual => {},
+        Ok(expected) if !update => panic!(
+            "snapshot `{}` doesn't match {}\ndiff (- snapshot, + actual):\n{}\n\
+             rerun with {}=1 to update it",
+            name, path.display(), line_diff(&expected, &actual), UPDATE_VAR
+        ),
+        _ => {
+            let written = path.parent().map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, &actual));
+            if let Err(err) = written {
+                panic!("snapshot `{}` can't be written to {}: {}", name, path.display(), err);
+            }
+        },
+    }
+}
+
+/// The lines of `expected` and `actual`, where lines that are only in
+/// `expected` start with `-`, those only in `actual` with `+`, and those
+/// in both with a space, based on their longest common subsequence.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+    let mut common = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0 .. a.len()).rev() {
+        for j in (0 .. b.len()).rev() {
+            common[i][j] = match a[i] == b[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            lines.push(format!("  {}", a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", a[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", b[j]));
+            j += 1;
+        }
+    }
+    lines.join("\n")
+}
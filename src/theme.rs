@@ -0,0 +1,173 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+/// The semantic role of a piece of stringified output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// Container labels and delimiters e.g. `Vec [`, `HashMap {` and `}`.
+    Container,
+    /// The names of struct and enum variant fields.
+    FieldName,
+    /// Integers and floats.
+    Number,
+    /// Strings and chars.
+    String,
+    /// Markers for values that could not be stringified e.g. `<locked>`.
+    Error,
+}
+
+/// An ANSI terminal color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl Color {
+    /// The SGR parameter that sets `self` as the foreground color.
+    pub fn ansi_code(&self) -> u8 {
+        match self {
+            Color::Black         => 30,
+            Color::Red           => 31,
+            Color::Green         => 32,
+            Color::Yellow        => 33,
+            Color::Blue          => 34,
+            Color::Magenta       => 35,
+            Color::Cyan          => 36,
+            Color::White         => 37,
+            Color::BrightBlack   => 90,
+            Color::BrightRed     => 91,
+            Color::BrightGreen   => 92,
+            Color::BrightYellow  => 93,
+            Color::BrightBlue    => 94,
+            Color::BrightMagenta => 95,
+            Color::BrightCyan    => 96,
+            Color::BrightWhite   => 97,
+        }
+    }
+}
+
+/// A mapping from each `Role` to the `Color` used to write it, if any.
+/// The default `Theme` is `Theme::plain()`, which writes no colors at all.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Theme {
+    pub container: Option<Color>,
+    pub field_name: Option<Color>,
+    pub number: Option<Color>,
+    pub string: Option<Color>,
+    pub error: Option<Color>,
+}
+
+impl Theme {
+    const RESET: &'static str = "\x1b[0m";
+
+    /// A `Theme` without any colors.
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// A `Theme` suited to terminals with a dark background.
+    pub fn dark() -> Self {
+        Self {
+            container:  Some(Color::BrightCyan),
+            field_name: Some(Color::BrightBlue),
+            number:     Some(Color::BrightYellow),
+            string:     Some(Color::BrightGreen),
+            error:      Some(Color::BrightRed),
+        }
+    }
+
+    /// A `Theme` suited to terminals with a light background.
+    pub fn light() -> Self {
+        Self {
+            container:  Some(Color::Cyan),
+            field_name: Some(Color::Blue),
+            number:     Some(Color::Magenta),
+            string:     Some(Color::Green),
+            error:      Some(Color::Red),
+        }
+    }
+
+    /// Return `self` if colored output is appropriate, and `Theme::plain()`
+    /// otherwise i.e. when stdout is not a terminal or when the `NO_COLOR`
+    /// environment variable is set to a non-empty value.
+    pub fn auto(self) -> Self {
+        self.auto_for(io::stdout().is_terminal())
+    }
+
+    /// Like `Theme::auto`, but for output to a stream that is a terminal
+    /// if `terminal` is `true`, e.g. `io::stderr().is_terminal()`.
+    pub fn auto_for(self, terminal: bool) -> Self {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        if no_color || !terminal {
+            return Self::plain();
+        }
+        self
+    }
+
+    pub fn color(&self, role: Role) -> Option<Color> {
+        match role {
+            Role::Container => self.container,
+            Role::FieldName => self.field_name,
+            Role::Number    => self.number,
+            Role::String    => self.string,
+            Role::Error     => self.error,
+        }
+    }
+
+    pub fn with_color(&self, role: Role, color: Option<Color>) -> Self {
+        let mut theme = *self;
+        match role {
+            Role::Container => theme.container = color,
+            Role::FieldName => theme.field_name = color,
+            Role::Number    => theme.number = color,
+            Role::String    => theme.string = color,
+            Role::Error     => theme.error = color,
+        }
+        theme
+    }
+
+    /// Start writing in the color for `role`, if any.
+    pub fn begin<W>(&self, buf: &mut W, role: Role) -> io::Result<()>
+    where W: Write {
+        match self.color(role) {
+            Some(color) => write!(buf, "\x1b[{}m", color.ansi_code()),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop writing in the color for `role`, if any.
+    pub fn end<W>(&self, buf: &mut W, role: Role) -> io::Result<()>
+    where W: Write {
+        match self.color(role) {
+            Some(_) => buf.write_all(Self::RESET.as_bytes()),
+            None => Ok(()),
+        }
+    }
+
+    /// Write `text` in the color for `role`, if any.
+    pub fn write<W>(&self, buf: &mut W, role: Role, text: &str) -> io::Result<()>
+    where W: Write {
+        self.begin(buf, role)?;
+        buf.write_all(text.as_bytes())?;
+        self.end(buf, role)
+    }
+}
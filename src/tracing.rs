@@ -0,0 +1,135 @@
+use crate::options::Layout;
+use crate::stringifier::Stringifier;
+use crate::stringify::Stringify2;
+use crate::styles::Styles;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::Arc;
+use tracing_core::field::{Field, Visit};
+use tracing_subscriber::field::{MakeVisitor, VisitFmt, VisitOutput};
+use tracing_subscriber::fmt::format::Writer;
+
+thread_local! {
+    /// The `Styles` of the `StringifyFields` that is recording a field
+    /// on this thread, if any.
+    static RECORDING: RefCell<Option<Arc<Styles>>> = const { RefCell::new(None) };
+}
+
+/// A `tracing` field value that is written by its `Stringify2` impl:
+/// ```text
+/// info!(config = ?Traced(&config), "loaded");
+/// ```
+/// When it is recorded by a subscriber that formats fields with
+/// `StringifyFields`, it is written with the `Styles` of that, and on
+/// a single line with the default `Styles` otherwise.
+pub struct Traced<'a, T: ?Sized>(pub &'a T);
+
+impl<T> fmt::Debug for Traced<'_, T>
+where T: Stringify2 + ?Sized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let styles = RECORDING.with(|recording| recording.borrow().clone());
+        let result = match styles {
+            Some(styles) => self.0.stringify_fmt(f, &styles),
+            None => {
+                let stringifier = Stringifier::default();
+                let options = stringifier.options().clone().with_layout(Layout::Compact);
+                self.0.stringify_fmt(f, &stringifier.styles().clone().with_options(options))
+            },
+        };
+        result.map_err(|_| fmt::Error)
+    }
+}
+
+impl<T> fmt::Display for Traced<'_, T>
+where T: Stringify2 + ?Sized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// A field formatter for `tracing-subscriber` that writes `Traced` values
+/// with its `Styles`, e.g. indented over multiple lines, rather than flat:
+/// ```text
+/// tracing_subscriber::fmt().fmt_fields(StringifyFields::default()).init();
+/// ```
+/// Fields are written like the default formatter does, i.e. the message
+/// first, followed by the other fields as `name=value`, separated by spaces.
+#[derive(Clone)]
+pub struct StringifyFields {
+    styles: Arc<Styles>,
+}
+
+impl StringifyFields {
+    pub fn new(styles: Styles) -> Self {
+        Self { styles: Arc::new(styles) }
+    }
+}
+
+/// A `StringifyFields` with the `Styles` of the default `Stringifier`.
+impl Default for StringifyFields {
+    fn default() -> Self {
+        Self::new(Stringifier::default().styles().clone())
+    }
+}
+
+impl<'w> MakeVisitor<Writer<'w>> for StringifyFields {
+    type Visitor = FieldVisitor<'w>;
+
+    fn make_visitor(&self, writer: Writer<'w>) -> Self::Visitor {
+        FieldVisitor { writer, styles: self.styles.clone(), empty: true, result: Ok(()) }
+    }
+}
+
+/// The visitor that writes the fields of a single span or event for
+/// `StringifyFields`.
+pub struct FieldVisitor<'w> {
+    writer: Writer<'w>,
+    styles: Arc<Styles>,
+    empty: bool,
+    result: fmt::Result,
+}
+
+impl FieldVisitor<'_> {
+    fn write_field(&mut self, field: &Field, value: fmt::Arguments) -> fmt::Result {
+        if !self.empty {
+            self.writer.write_char(' ')?;
+        }
+        self.empty = false;
+        match field.name() {
+            "message" => self.writer.write_fmt(value),
+            name => write!(self.writer, "{}={}", name, value),
+        }
+    }
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.result.is_ok() {
+            self.result = match field.name() {
+                "message" => self.write_field(field, format_args!("{}", value)),
+                _ => self.write_field(field, format_args!("{:?}", value)),
+            };
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.result.is_ok() {
+            let styles = Some(self.styles.clone());
+            let previous = RECORDING.with(|recording| recording.replace(styles));
+            self.result = self.write_field(field, format_args!("{:?}", value));
+            RECORDING.with(|recording| *recording.borrow_mut() = previous);
+        }
+    }
+}
+
+impl VisitOutput<fmt::Result> for FieldVisitor<'_> {
+    fn finish(self) -> fmt::Result {
+        self.result
+    }
+}
+
+impl VisitFmt for FieldVisitor<'_> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        &mut self.writer
+    }
+}
@@ -0,0 +1,74 @@
+use crate::context::StringifyContext;
+use crate::error::StringifyResult;
+use crate::newline::Newline;
+use crate::node::Node;
+use crate::options::Layout;
+use crate::stringify::Stringify2;
+use crate::styles::{StyleKey, Styles};
+use std::fmt::Debug;
+use std::io::Write;
+
+/// An adapter that stringifies a value by its `Debug` impl, so that types
+/// from other crates without a `Stringify2` impl can be part of a larger
+/// stringification. The `{:#?}` output is written line by line, with each
+/// line after the first one indented like the lines of the `"end"` style
+/// at the current depth. With `Layout::Compact`, or if the context is flat,
+/// the `{:?}` output is written instead.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ViaDebug<T: ?Sized>(pub T);
+
+impl<T> Stringify2 for ViaDebug<T>
+where T: Debug + ?Sized {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        if styles.options().layout == Layout::Compact || ctx.is_flat() {
+            write!(buf, "{:?}", &self.0)?;
+            return Ok(());
+        }
+        let mut line = styles.get_or_default(StyleKey::End);
+        line.newline = Newline::Add;
+        line.indent_level = 0;
+        let text = format!("{:#?}", &self.0);
+        for (idx, text) in text.lines().enumerate() {
+            if idx > 0 {
+                self.indent(buf, styles, ctx, &line)?;
+            }
+            buf.write_all(text.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn to_node(&self, _: &Styles) -> StringifyResult<Node> {
+        Ok(Node::Text(format!("{:?}", &self.0)))
+    }
+}
+
+/// Implement `Stringify2` for each of the given types by their `Debug`
+/// impl, as `ViaDebug` does. The orphan rule applies as usual, so foreign
+/// types must be wrapped in `ViaDebug` instead:
+/// ```text
+/// stringify_via_debug!(Session, Handle<u32>);
+/// ```
+#[macro_export]
+macro_rules! stringify_via_debug {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl $crate::Stringify2 for $ty {
+                fn stringify<W>(&self,
+                                buf: &mut W,
+                                styles: &$crate::Styles,
+                                ctx: &mut $crate::StringifyContext) -> $crate::StringifyResult<()>
+                where W: ::std::io::Write {
+                    $crate::Stringify2::stringify(&$crate::ViaDebug(self), buf, styles, ctx)
+                }
+
+                fn to_node(&self, styles: &$crate::Styles) -> $crate::StringifyResult<$crate::Node> {
+                    $crate::Stringify2::to_node(&$crate::ViaDebug(self), styles)
+                }
+            }
+        )+
+    };
+}
@@ -0,0 +1,635 @@
+//! Derive macros for the `stringify` crate.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DataEnum, DeriveInput, ExprPath, Fields,
+    Generics, Ident, Index, LitStr, Token, WherePredicate
+};
+
+
+/// Derive an implementation of `Stringify2` for a struct or an enum.
+///
+/// The generated impl uses the following entries of the `Styles` it is given,
+/// falling back to their defaults (see `Styles::get_or_default`):
+/// - `"start"` is used before the struct or variant name
+/// - `"name"` is used before each named field (see `stringify_field`)
+/// - `"end"` is used before the closing brace of named fields
+///
+/// Enum variants are printed as `Type::Variant`, followed by their
+/// payload in the same layout as a struct with the same shape. Without any
+/// named fields to write, a struct or variant is written as `Name {}`.
+///
+/// For generic types, a `T: Stringify2` bound is added for each type
+/// parameter `T`. The struct or enum can be annotated with
+/// `#[stringify(bound = "T: Trait, ...")]` to use the given bounds
+/// instead, e.g. `#[stringify(bound = "")]` to add none at all.
+///
+/// With `#[stringify(align)]` on the struct or enum, named fields are
+/// written with a `stringify::FieldBlock`, which pads their names so that
+/// the values line up, and `#[stringify(align = " = ")]` also writes the
+/// given separator between the names and the values. It can't be combined
+/// with flattened fields.
+///
+/// Fields can be annotated with `#[stringify(...)]` attributes:
+/// - `skip` leaves the field out of the output
+/// - `rename = "name"` writes the named field as `name`
+/// - `with = path` writes the field with the function at `path` rather than
+///   its `Stringify2` impl. Its signature must be
+///   `fn(&T, &mut dyn Write, &Styles, &mut StringifyContext) -> StringifyResult<()>`
+///   where `T` is the type of the field.
+/// - `flatten` writes the fields of the named field as if they were fields of
+///   the struct or variant itself (see `Stringify2::stringify_flattened`)
+/// - `redact` writes the field as a `stringify::Redacted` value, so that
+///   secrets don't end up in the output
+/// - `skip_if_default` leaves the named field out of the output if it is
+///   equal to `Default::default()`, which requires `Default + PartialEq`
+///
+/// With `Options::skip_unset`, named fields whose values are unset are left
+/// out as well (see `Stringify2::is_unset`), where a struct with named fields
+/// is unset if all of its fields are.
+#[proc_macro_derive(Stringify2, attributes(stringify))]
+pub fn derive_stringify2(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let (body, node, flattened) = match &input.data {
+        Data::Struct(data) => {
+            let attrs = FieldAttrs::parse_all(&data.fields)?;
+            container.check(ident, &attrs)?;
+            let values = struct_values(&data.fields);
+            let label = ident.to_string();
+            let body = stringify_fields(&label, &data.fields, &attrs, &values, &container);
+            let node = node_fields(&label, &data.fields, &attrs, &values);
+            let flattened = match &data.fields {
+                Fields::Named(_) => {
+                    let is_unset = struct_is_unset(&attrs, &values);
+                    let (written, fields) = named_fields(&data.fields, &attrs, &values, true);
+                    let last = match fields.is_empty() {
+                        true => quote!(_),
+                        false => quote!(last),
+                    };
+                    quote! {
+                        fn stringify_flattened<W>(&self,
+                                                  buf: &mut W,
+                                                  styles: &::stringify::Styles,
+                                                  ctx: &mut ::stringify::StringifyContext,
+                                                  _: &str,
+                                                  #last: bool)
+                                                  -> ::stringify::StringifyResult<()>
+                        where W: ::std::io::Write {
+                            #written
+                            #(#fields)*
+                            Ok(())
+                        }
+
+                        #is_unset
+                    }
+                },
+                _ => quote!(),
+            };
+            (body, node, flattened)
+        },
+        Data::Enum(data) => (
+            match_variants(ident, data, |label, fields, attrs, values| {
+                container.check(ident, attrs)?;
+                Ok(stringify_fields(label, fields, attrs, values, &container))
+            })?,
+            match_variants(ident, data, |label, fields, attrs, values| {
+                Ok(node_fields(label, fields, attrs, values))
+            })?,
+            quote!(),
+        ),
+        Data::Union(_) => return Err(syn::Error::new_spanned(
+            ident,
+            "#[derive(Stringify2)] is not supported for unions"
+        )),
+    };
+    // Generic types may not be 'static, so only non-generic types can
+    // take part in the formatter registry of the `Options`.
+    let as_any = match input.generics.params.is_empty() {
+        true => quote! {
+            fn as_any(&self) -> ::std::option::Option<&dyn ::std::any::Any> {
+                ::std::option::Option::Some(self)
+            }
+        },
+        false => quote!(),
+    };
+    let generics = add_bounds(&input.generics, container.bound);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::stringify::Stringify2 for #ident #ty_generics
+        #where_clause {
+            fn stringify<W>(&self,
+                            buf: &mut W,
+                            styles: &::stringify::Styles,
+                            ctx: &mut ::stringify::StringifyContext)
+                            -> ::stringify::StringifyResult<()>
+            where W: ::std::io::Write {
+                #body
+            }
+
+            #flattened
+
+            fn to_node(&self, styles: &::stringify::Styles)
+                       -> ::stringify::StringifyResult<::stringify::Node> {
+                #node
+            }
+
+            #as_any
+        }
+    })
+}
+
+/// The options given to a struct or enum with `#[stringify(...)]` attributes.
+#[derive(Default)]
+struct ContainerAttrs {
+    bound: Option<Punctuated<WherePredicate, Token![,]>>,
+    align: bool,
+    separator: Option<String>,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container_attrs = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("stringify")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    let predicates: LitStr = meta.value()?.parse()?;
+                    container_attrs.bound = Some(predicates.parse_with(
+                        Punctuated::<WherePredicate, Token![,]>::parse_terminated
+                    )?);
+                } else if meta.path.is_ident("align") {
+                    container_attrs.align = true;
+                    if meta.input.peek(Token![=]) {
+                        let separator: LitStr = meta.value()?.parse()?;
+                        container_attrs.separator = Some(separator.value());
+                    }
+                } else {
+                    return Err(meta.error("unsupported #[stringify] attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(container_attrs)
+    }
+
+    /// Check that `self` can be applied to the fields with `attrs` of the
+    /// struct or enum `ident`.
+    fn check(&self, ident: &Ident, attrs: &[FieldAttrs]) -> syn::Result<()> {
+        match self.align && attrs.iter().any(|attrs| attrs.flatten && !attrs.skip) {
+            true => Err(syn::Error::new_spanned(ident, "align can't be combined with flatten")),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Add the `bound` of the `#[stringify(bound = "...")]` attribute to the
+/// where clause of `generics`, or if there is no such attribute,
+/// a `T: Stringify2` bound for each type parameter `T`.
+fn add_bounds(generics: &Generics,
+              bound: Option<Punctuated<WherePredicate, Token![,]>>) -> Generics {
+    let predicates: Vec<WherePredicate> = match bound {
+        Some(predicates) => predicates.into_iter().collect(),
+        None => generics.type_params()
+            .map(|param| {
+                let ident = &param.ident;
+                parse_quote!(#ident: ::stringify::Stringify2)
+            })
+            .collect(),
+    };
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// The expressions that evaluate to references to the fields of `self`.
+fn struct_values(fields: &Fields) -> Vec<TokenStream2> {
+    match fields {
+        Fields::Named(named) => named.named.iter()
+            .map(|field| {
+                let ident = field.ident.as_ref().unwrap();
+                quote!(&self.#ident)
+            })
+            .collect(),
+        Fields::Unnamed(unnamed) => (0 .. unnamed.unnamed.len())
+            .map(|i| {
+                let index = Index::from(i);
+                quote!(&self.#index)
+            })
+            .collect(),
+        Fields::Unit => vec![],
+    }
+}
+
+/// Generate a `match` on `self` with an arm for each variant of the enum,
+/// whose body is generated by `arm` like `stringify_fields` does.
+fn match_variants<F>(ident: &Ident, data: &DataEnum, arm: F) -> syn::Result<TokenStream2>
+where F: Fn(&str, &Fields, &[FieldAttrs], &[TokenStream2]) -> syn::Result<TokenStream2> {
+    if data.variants.is_empty() {
+        return Ok(quote! { match *self {} });
+    }
+    let arms = data.variants.iter().map(|variant| {
+        let var_ident = &variant.ident;
+        let label = format!("{}::{}", ident, var_ident);
+        let attrs = FieldAttrs::parse_all(&variant.fields)?;
+        let (pattern, values) = match &variant.fields {
+            Fields::Named(named) => {
+                // Skipped fields are left out of the pattern, so that
+                // they don't end up as unused bindings.
+                let idents: Vec<&Ident> = named.named.iter().zip(&attrs)
+                    .filter(|(_, attrs)| !attrs.skip)
+                    .map(|(field, _)| field.ident.as_ref().unwrap())
+                    .collect();
+                let rest = match idents.len() < named.named.len() {
+                    true => quote!(..),
+                    false => quote!(),
+                };
+                let values = named.named.iter().zip(&attrs)
+                    .map(|(field, attrs)| match attrs.skip {
+                        true => quote!(),
+                        false => {
+                            let ident = field.ident.as_ref().unwrap();
+                            quote!(#ident)
+                        },
+                    })
+                    .collect();
+                (quote!(#ident::#var_ident { #(#idents,)* #rest }), values)
+            },
+            Fields::Unnamed(unnamed) => {
+                let idents: Vec<Ident> = (0 .. unnamed.unnamed.len())
+                    .map(|i| format_ident!("__field{}", i))
+                    .collect();
+                let bindings = idents.iter().zip(&attrs).map(|(ident, attrs)| match attrs.skip {
+                    true => quote!(_),
+                    false => quote!(#ident),
+                });
+                let values = idents.iter().map(|ident| quote!(#ident)).collect();
+                (quote!(#ident::#var_ident ( #(#bindings),* )), values)
+            },
+            Fields::Unit => (quote!(#ident::#var_ident), vec![]),
+        };
+        let body = arm(&label, &variant.fields, &attrs, &values)?;
+        Ok(quote! { #pattern => { #body } })
+    }).collect::<syn::Result<Vec<_>>>()?;
+    Ok(quote! {
+        match self {
+            #(#arms)*
+        }
+    })
+}
+
+/// The options given to a field with `#[stringify(...)]` attributes.
+#[derive(Default)]
+struct FieldAttrs {
+    skip: bool,
+    rename: Option<String>,
+    with: Option<ExprPath>,
+    flatten: bool,
+    redact: bool,
+    skip_if_default: bool,
+}
+
+impl FieldAttrs {
+    /// Parse the attributes of each of `fields`.
+    fn parse_all(fields: &Fields) -> syn::Result<Vec<Self>> {
+        let named = matches!(fields, Fields::Named(_));
+        fields.iter().map(|field| Self::parse(&field.attrs, named)).collect()
+    }
+
+    fn parse(attrs: &[Attribute], named: bool) -> syn::Result<Self> {
+        let mut field_attrs = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("stringify")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    field_attrs.skip = true;
+                } else if meta.path.is_ident("rename") && named {
+                    let name: LitStr = meta.value()?.parse()?;
+                    field_attrs.rename = Some(name.value());
+                } else if meta.path.is_ident("with") {
+                    field_attrs.with = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("flatten") && named {
+                    field_attrs.flatten = true;
+                } else if meta.path.is_ident("redact") {
+                    field_attrs.redact = true;
+                } else if meta.path.is_ident("skip_if_default") && named {
+                    field_attrs.skip_if_default = true;
+                } else if meta.path.is_ident("rename") || meta.path.is_ident("flatten") {
+                    return Err(meta.error("only named fields can be renamed or flattened"));
+                } else if meta.path.is_ident("skip_if_default") {
+                    return Err(meta.error("only named fields can be skipped if default"));
+                } else {
+                    return Err(meta.error("unsupported #[stringify] attribute"));
+                }
+                if field_attrs.flatten && (field_attrs.with.is_some() || field_attrs.redact) {
+                    return Err(meta.error("flatten can't be combined with with or redact"));
+                }
+                if field_attrs.flatten && field_attrs.skip_if_default {
+                    return Err(meta.error("flatten can't be combined with skip_if_default"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(field_attrs)
+    }
+
+    /// Generate an expression that tells whether the field is written,
+    /// where `value` evaluates to a reference to the value of the field.
+    fn written(&self, value: &TokenStream2) -> TokenStream2 {
+        let wrapped = self.wrap(value);
+        let set = quote! {
+            !(styles.options().skip_unset && ::stringify::Stringify2::is_unset(#wrapped))
+        };
+        match self.skip_if_default {
+            true => quote!(!::stringify::__is_default(#value) && #set),
+            false => set,
+        }
+    }
+
+    /// Wrap `value`, an expression that evaluates to a reference to the
+    /// value of the field, according to `self`.
+    fn wrap(&self, value: &TokenStream2) -> TokenStream2 {
+        let value = match &self.with {
+            Some(with) => quote!(&::stringify::__With { value: #value, with: #with }),
+            None => value.clone(),
+        };
+        match self.redact {
+            true => quote!(&::stringify::Redacted(#value)),
+            false => value,
+        }
+    }
+}
+
+/// Generate the statements that write the named `fields` that aren't skipped,
+/// preceded by the declaration of `__written`, which tells whether each of
+/// them is written. Each element of `values` is an expression that
+/// evaluates to a reference to the value of the corresponding element of
+/// `fields`. If the fields are `flattened` into another value, the last one
+/// is followed by a separator only if the `last` argument of
+/// `stringify_flattened` is `true`. Otherwise their names are padded to the
+/// width of the widest one that is written with `Options::align`, which is
+/// left to the value they're flattened into.
+fn named_fields(fields: &Fields,
+                attrs: &[FieldAttrs],
+                values: &[TokenStream2],
+                flattened: bool) -> (TokenStream2, Vec<TokenStream2>) {
+    let written: Vec<_> = fields.iter().zip(attrs).zip(values)
+        .filter(|((_, attrs), _)| !attrs.skip)
+        .map(|((field, attrs), value)| {
+            let field_name = attrs.rename.clone()
+                .unwrap_or_else(|| field.ident.as_ref().unwrap().to_string());
+            (field_name, attrs, value)
+        })
+        .collect();
+    if written.is_empty() {
+        return (quote!(), vec![]);
+    }
+    // Whether each field is written, which is only known at runtime, so that
+    // the last field that is written can be followed by the right separator.
+    let conditions = written.iter().map(|(_, attrs, value)| attrs.written(value));
+    let declaration = quote! {
+        let __written = [#(#conditions),*];
+    };
+    let fields: Vec<_> = written.iter().enumerate().map(|(i, (field_name, attrs, value))| {
+        let value = attrs.wrap(value);
+        let next = i + 1;
+        let last = match flattened {
+            true => quote!(last && !__written[#next ..].contains(&true)),
+            false => quote!(!__written[#next ..].contains(&true)),
+        };
+        match attrs.flatten {
+            true => quote! {
+                ::stringify::Stringify2::stringify_flattened(
+                    #value, buf, styles, ctx, #field_name, #last
+                )?;
+            },
+            false => quote! {
+                if __written[#i] {
+                    self.stringify_field(buf, styles, ctx, #field_name, #value)?;
+                    self.write_separator(buf, styles, ctx, #last)?;
+                }
+            },
+        }
+    }).collect();
+    if flattened {
+        return (declaration, fields);
+    }
+    let names = written.iter().enumerate()
+        .filter(|(_, (_, attrs, _))| !attrs.flatten)
+        .map(|(i, (field_name, _, _))| quote!((#field_name, __written[#i])));
+    let statement = quote! {
+        let __width = ::stringify::__name_width([#(#names),*]);
+        ctx.with_name_width(__width, |ctx| {
+            #(#fields)*
+            Ok::<_, ::stringify::StringifyError>(())
+        })?;
+    };
+    (declaration, vec![statement])
+}
+
+/// Generate the `is_unset` method of a struct with named fields, which is
+/// unset if all of its fields that aren't skipped are, so that e.g. a section
+/// of a config that is not set at all is left out with `Options::skip_unset`.
+fn struct_is_unset(attrs: &[FieldAttrs], values: &[TokenStream2]) -> TokenStream2 {
+    let unset: Vec<_> = attrs.iter().zip(values)
+        .filter(|(attrs, _)| !attrs.skip)
+        .map(|(attrs, value)| {
+            let wrapped = attrs.wrap(value);
+            match attrs.skip_if_default {
+                true => quote! {
+                    (::stringify::__is_default(#value) || ::stringify::Stringify2::is_unset(#wrapped))
+                },
+                false => quote!(::stringify::Stringify2::is_unset(#wrapped)),
+            }
+        })
+        .collect();
+    if unset.is_empty() {
+        return quote!();
+    }
+    quote! {
+        fn is_unset(&self) -> bool {
+            #(#unset)&&*
+        }
+    }
+}
+
+/// Generate the code that stringifies a struct or enum variant named `label`.
+/// Each element of `values` is an expression that evaluates to a reference
+/// to the value of the corresponding element of `fields`.
+fn stringify_fields(label: &str,
+                    fields: &Fields,
+                    attrs: &[FieldAttrs],
+                    values: &[TokenStream2],
+                    container: &ContainerAttrs) -> TokenStream2 {
+    match fields {
+        Fields::Named(_) if container.align => {
+            let open = format!("{} {{", label);
+            let fields = fields.iter().zip(attrs).zip(values)
+                .filter(|((_, attrs), _)| !attrs.skip)
+                .map(|((field, attrs), value)| {
+                    let field_name = attrs.rename.clone()
+                        .unwrap_or_else(|| field.ident.as_ref().unwrap().to_string());
+                    let wrapped = attrs.wrap(value);
+                    match attrs.skip_if_default {
+                        true => quote! {
+                            .field_if(!::stringify::__is_default(#value), #field_name, #wrapped)
+                        },
+                        false => quote!(.field(#field_name, #wrapped)),
+                    }
+                });
+            let separator = container.separator.as_ref()
+                .map(|separator| quote!(.with_separator(#separator)));
+            let empty = format!("{} {{}}", label);
+            quote! {
+                self.indent(buf, styles, ctx, &styles.get_or_default(::stringify::StyleKey::Start))?;
+                let __block = ::stringify::FieldBlock::new()
+                    #(#fields)*
+                    #separator;
+                if __block.is_empty(styles) {
+                    styles.options().theme.write(buf, ::stringify::Role::Container, #empty)?;
+                    return Ok(());
+                }
+                styles.options().theme.write(buf, ::stringify::Role::Container, #open)?;
+                __block.stringify(self, buf, styles, ctx)?;
+                self.indent(buf, styles, ctx, &styles.get_or_default(::stringify::StyleKey::End))?;
+                styles.options().theme.write(buf, ::stringify::Role::Container, "}")?;
+                Ok(())
+            }
+        },
+        Fields::Named(_) => {
+            let open = format!("{} {{", label);
+            let empty = format!("{} {{}}", label);
+            let (written, fields) = named_fields(fields, attrs, values, false);
+            if fields.is_empty() {
+                return quote! {
+                    self.indent(buf, styles, ctx, &styles.get_or_default(::stringify::StyleKey::Start))?;
+                    styles.options().theme.write(buf, ::stringify::Role::Container, #empty)?;
+                    Ok(())
+                };
+            }
+            quote! {
+                self.indent(buf, styles, ctx, &styles.get_or_default(::stringify::StyleKey::Start))?;
+                #written
+                if !__written.contains(&true) {
+                    styles.options().theme.write(buf, ::stringify::Role::Container, #empty)?;
+                    return Ok(());
+                }
+                styles.options().theme.write(buf, ::stringify::Role::Container, #open)?;
+                #(#fields)*
+                self.indent(buf, styles, ctx, &styles.get_or_default(::stringify::StyleKey::End))?;
+                styles.options().theme.write(buf, ::stringify::Role::Container, "}")?;
+                Ok(())
+            }
+        },
+        Fields::Unnamed(_) => {
+            let open = format!("{}(", label);
+            let fields = attrs.iter().zip(values).enumerate()
+                .filter(|(_, (attrs, _))| !attrs.skip)
+                .enumerate()
+                .map(|(n, (i, (attrs, value)))| {
+                    let sep = if n == 0 { quote!() } else {
+                        quote! {
+                            buf.write_all(styles.options().delimiters.separator.as_bytes())?;
+                            buf.write_all(" ".as_bytes())?;
+                        }
+                    };
+                    let value = attrs.wrap(value);
+                    quote! {
+                        #sep
+                        ctx.inline(::stringify::PathSegment::Index(#i), |ctx| {
+                            ::stringify::Stringify2::stringify_component(#value, buf, styles, ctx)
+                        })?;
+                    }
+                });
+            quote! {
+                self.indent(buf, styles, ctx, &styles.get_or_default(::stringify::StyleKey::Start))?;
+                styles.options().theme.write(buf, ::stringify::Role::Container, #open)?;
+                #(#fields)*
+                styles.options().theme.write(buf, ::stringify::Role::Container, ")")?;
+                Ok(())
+            }
+        },
+        Fields::Unit => quote! {
+            self.indent(buf, styles, ctx, &styles.get_or_default(::stringify::StyleKey::Start))?;
+            styles.options().theme.write(buf, ::stringify::Role::Container, #label)?;
+            Ok(())
+        },
+    }
+}
+
+/// Generate the code that describes a struct or enum variant named `label`
+/// as a `Node`, like `stringify_fields` does for stringifying it.
+fn node_fields(label: &str,
+               fields: &Fields,
+               attrs: &[FieldAttrs],
+               values: &[TokenStream2]) -> TokenStream2 {
+    let written = fields.iter().zip(attrs).zip(values)
+        .filter(|((_, attrs), _)| !attrs.skip);
+    match fields {
+        Fields::Named(_) => {
+            let fields: Vec<_> = written.map(|((field, attrs), raw)| {
+                let field_name = attrs.rename.clone()
+                    .unwrap_or_else(|| field.ident.as_ref().unwrap().to_string());
+                let value = attrs.wrap(raw);
+                match attrs.flatten {
+                    true => quote! {
+                        ::stringify::Node::of(#value, styles)?
+                            .__flatten_into(#field_name, &mut fields);
+                    },
+                    false if attrs.skip_if_default => quote! {
+                        if !::stringify::__is_default(#raw) {
+                            fields.push((
+                                ::std::borrow::Cow::Borrowed(#field_name),
+                                ::stringify::Node::of(#value, styles)?,
+                            ));
+                        }
+                    },
+                    false => quote! {
+                        fields.push((
+                            ::std::borrow::Cow::Borrowed(#field_name),
+                            ::stringify::Node::of(#value, styles)?,
+                        ));
+                    },
+                }
+            }).collect();
+            let mutability = match fields.is_empty() {
+                true => quote!(),
+                false => quote!(mut),
+            };
+            quote! {
+                let #mutability fields = ::std::vec::Vec::new();
+                #(#fields)*
+                Ok(::stringify::Node::Struct {
+                    label: ::std::borrow::Cow::Borrowed(#label),
+                    fields,
+                })
+            }
+        },
+        Fields::Unnamed(_) => {
+            let items = written.map(|((_, attrs), value)| {
+                let value = attrs.wrap(value);
+                quote!(::stringify::Node::of(#value, styles)?)
+            });
+            quote! {
+                Ok(::stringify::Node::Tuple {
+                    label: ::std::borrow::Cow::Borrowed(#label),
+                    items: ::std::vec![#(#items),*],
+                })
+            }
+        },
+        Fields::Unit => quote! {
+            Ok(::stringify::Node::Unit(::std::borrow::Cow::Borrowed(#label)))
+        },
+    }
+}
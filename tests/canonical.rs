@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::io::Write;
+use stringify::{
+    Multiline, Options, Quoting, StyleKey, Stringifier, Stringify2, StringifyContext,
+    StringifyResult, Styles
+};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where T: Stringify2 + ?Sized {
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+/// A key whose stringification doesn't tell its instances apart.
+#[derive(PartialEq, Eq, Hash)]
+struct Key(u8);
+
+impl Stringify2 for Key {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    _styles: &Styles,
+                    _ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        write!(buf, "key")?;
+        Ok(())
+    }
+}
+
+/// A value that is written on a line of its own, after an empty line.
+struct Spaced(u8);
+
+impl Stringify2 for Spaced {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let ctx = ctx.indented();
+        self.indent(buf, styles, &ctx, &styles.get_or_default(StyleKey::Name))?;
+        self.indent(buf, styles, &ctx, &styles.get_or_default(StyleKey::Name))?;
+        write!(buf, "{}", self.0)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn canonical() {
+    let mut map = HashMap::new();
+    for (key, value) in [("c", 3.5), ("a", 1.0), ("b", 2.25)].iter().copied() {
+        map.insert(key, value);
+    }
+    let expected = "HashMap {\n    a : 1,\n    b : 2.25,\n    c : 3.5,\n}";
+    assert_eq!(stringify(&map, Options::canonical()), expected);
+    assert_eq!(Stringifier::canonical().to_string(&map).unwrap(), expected);
+}
+
+#[test]
+fn canonical_entries_with_equal_keys_are_sorted_by_value() {
+    let mut map = HashMap::new();
+    for (key, value) in [(1, 30), (2, 10), (3, 20)].iter().copied() {
+        map.insert(Key(key), value);
+    }
+    let expected = "HashMap {\n    key : 10,\n    key : 20,\n    key : 30,\n}";
+    assert_eq!(stringify(&map, Options::canonical()), expected);
+}
+
+#[test]
+fn canonical_keeps_whitespace_of_values() {
+    let values = vec!["a ".to_string(), "a".to_string()];
+    assert_eq!(stringify(&values, Options::canonical()), "Vec [\n    a ,\n    a,\n]");
+}
+
+#[test]
+fn only_canonical_output_is_trimmed() {
+    let value = Spaced(1);
+    assert_eq!(stringify(&value, Options::default()), "\n        \n        1");
+    assert_eq!(stringify(&value, Options::canonical()), "\n\n        1");
+}
+
+#[test]
+fn margin_strings_start_on_the_line_of_an_element() {
+    let options = Options::default()
+        .with_quoting(Quoting::Raw)
+        .with_multiline(Multiline::Margin);
+    let values = vec!["a\nb".to_string()];
+    assert_eq!(stringify(&values, options.clone()), "Vec [\n    | a\n    | b,\n]");
+    assert_eq!(stringify(&values, options.with_canonical(true)), "Vec [\n    | a\n    | b,\n]");
+}
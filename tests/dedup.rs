@@ -0,0 +1,38 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use stringify::{Layout, Options, Stringifier, Stringify2};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where
+    T: Stringify2 + ?Sized,
+{
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+#[test]
+fn shared_values_are_written_once() {
+    let shared = Rc::new(vec![1, 2]);
+    let value = vec![shared.clone(), Rc::new(vec![1, 2]), shared];
+    let options = Options::default().with_layout(Layout::Compact);
+    assert_eq!(stringify(&value, options.clone()), "Vec [Vec [1, 2], Vec [1, 2], Vec [1, 2]]");
+    assert_eq!(
+        stringify(&value, options.with_dedup(true)),
+        "Vec [Vec [1, 2], Vec [1, 2], <same as [0]>]",
+    );
+}
+
+#[test]
+fn arcs_are_deduplicated() {
+    let shared = Arc::new("text");
+    let value = (shared.clone(), Some(shared));
+    let options = Options::default().with_layout(Layout::Compact).with_dedup(true);
+    assert_eq!(stringify(&value, options), "(text, Some(<same as [0]>))");
+}
+
+#[test]
+fn fitted_values_are_not_references_to_themselves() {
+    let shared = Rc::new(1);
+    let value = vec![shared.clone(), shared];
+    let options = Options::default().with_layout(Layout::Fit { width: 80 }).with_dedup(true);
+    assert_eq!(stringify(&value, options), "Vec [1, <same as [0]>]");
+}
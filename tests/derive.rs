@@ -0,0 +1,175 @@
+#![cfg(feature = "derive")]
+
+use std::io::Write;
+use stringify::{Layout, Node, Options, Stringifier, Stringify2, StringifyContext, StringifyResult, Styles};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where T: Stringify2 + ?Sized {
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+#[derive(Stringify2)]
+struct Point {
+    x: u8,
+    y: u8,
+}
+
+#[derive(Stringify2)]
+struct Empty {}
+
+#[test]
+fn structs() {
+    let point = Point { x: 1, y: 2 };
+    assert_eq!(stringify(&point, Options::default()), "Point {\n    x=1,\n    y=2,\n}");
+    assert_eq!(stringify(&point, Options::default().with_layout(Layout::Compact)), "Point {x=1, y=2}");
+}
+
+#[test]
+fn structs_without_fields() {
+    assert_eq!(stringify(&Empty {}, Options::default()), "Empty {}");
+    assert_eq!(stringify(&Empty {}, Options::default().with_layout(Layout::Compact)), "Empty {}");
+}
+
+#[derive(Stringify2)]
+struct Job {
+    args: Option<Vec<u8>>,
+    retries: Option<u8>,
+}
+
+#[test]
+fn option_fields() {
+    let job = Job { args: Some(vec![1, 2]), retries: None };
+    assert_eq!(stringify(&job, Options::default()), "Job {
+    args=Some(Vec [
+        1,
+        2,
+    ]),
+    retries=None,
+}");
+    let compact = Options::default().with_layout(Layout::Compact);
+    assert_eq!(stringify(&job, compact), "Job {args=Some(Vec [1, 2]), retries=None}");
+}
+
+#[derive(Stringify2)]
+struct Wrap(Vec<u8>);
+
+#[derive(Stringify2)]
+enum Event {
+    Batch(Vec<u8>, bool),
+}
+
+#[test]
+fn tuple_structs() {
+    assert_eq!(stringify(&Wrap(vec![1]), Options::default()), "Wrap(Vec [\n    1,\n])");
+}
+
+#[test]
+fn tuple_variants() {
+    let events = vec![Event::Batch(vec![1, 2], true)];
+    let expected = "Vec [
+    Event::Batch(Vec [
+        1,
+        2,
+    ], true),
+]";
+    assert_eq!(stringify(&events, Options::default()), expected);
+    let node = Node::of(&events, &Styles::default()).unwrap();
+    assert_eq!(stringify(&node, Options::default()), expected);
+}
+
+#[derive(Stringify2)]
+enum Shape {
+    Dot,
+    Line(u8, bool),
+    Rect { width: u8 },
+}
+
+#[test]
+fn enums() {
+    let shapes = vec![Shape::Dot, Shape::Line(1, true), Shape::Rect { width: 2 }];
+    let expected = "Vec [
+    Shape::Dot,
+    Shape::Line(1, true),
+    Shape::Rect {
+        width=2,
+    },
+]";
+    assert_eq!(stringify(&shapes, Options::default()), expected);
+    let compact = Options::default().with_layout(Layout::Compact);
+    assert_eq!(stringify(&shapes, compact), "Vec [Shape::Dot, Shape::Line(1, true), Shape::Rect {width=2}]");
+}
+
+fn hex(value: &u16,
+       buf: &mut dyn Write,
+       _styles: &Styles,
+       _ctx: &mut StringifyContext) -> StringifyResult<()> {
+    write!(buf, "{:#06x}", value)?;
+    Ok(())
+}
+
+#[derive(Stringify2)]
+struct Account {
+    #[stringify(skip)]
+    _id: u64,
+    #[stringify(rename = "kind")]
+    ty: &'static str,
+    #[stringify(with = hex)]
+    flags: u16,
+    #[stringify(flatten)]
+    position: Point,
+    #[stringify(redact)]
+    password: String,
+    #[stringify(skip_if_default)]
+    retries: u8,
+}
+
+fn account(retries: u8) -> Account {
+    Account {
+        _id: 7,
+        ty: "admin",
+        flags: 0x2a,
+        position: Point { x: 1, y: 2 },
+        password: "hunter2".to_string(),
+        retries,
+    }
+}
+
+#[test]
+fn attributes() {
+    let expected = "Account {
+    kind=admin,
+    flags=0x002a,
+    x=1,
+    y=2,
+    password=***,
+}";
+    assert_eq!(stringify(&account(0), Options::default()), expected);
+}
+
+#[test]
+fn skip_if_default_keeps_other_values() {
+    let compact = Options::default().with_layout(Layout::Compact);
+    let expected = "Account {kind=admin, flags=0x002a, x=1, y=2, password=***, retries=3}";
+    assert_eq!(stringify(&account(3), compact), expected);
+}
+
+#[derive(Stringify2)]
+#[stringify(align)]
+struct Aligned {
+    a: u8,
+    longer: u8,
+    #[stringify(skip_if_default)]
+    longest_name: u8,
+}
+
+#[test]
+fn align_pads_the_names_that_are_written() {
+    let options = Options::default();
+    let short = Aligned { a: 1, longer: 2, longest_name: 0 };
+    assert_eq!(stringify(&short, options.clone()), "Aligned {\n    a     =1,\n    longer=2,\n}");
+    let long = Aligned { a: 1, longer: 2, longest_name: 3 };
+    let expected = "Aligned {\n    a           =1,\n    longer      =2,\n    longest_name=3,\n}";
+    assert_eq!(stringify(&long, options.clone()), expected);
+    let compact = options.with_layout(Layout::Compact);
+    assert_eq!(stringify(&long, compact), "Aligned {a=1, longer=2, longest_name=3}");
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use stringify::{stringify_delta, stringify_diff, Elision, Node, Options, Styles};
+
+fn config(port: u16) -> BTreeMap<&'static str, u16> {
+    let mut config = BTreeMap::new();
+    config.insert("port", port);
+    config.insert("workers", 4);
+    config
+}
+
+fn record(fields: &[(&'static str, &str)]) -> Node {
+    let fields = fields.iter()
+        .map(|&(name, value)| (name.into(), Node::Number(value.to_string())))
+        .collect();
+    Node::Struct { label: "Record".into(), fields }
+}
+
+#[test]
+fn diff() {
+    let styles = Styles::default();
+    let expected = "  BTreeMap {\n-     port : 80,\n+     port : 8080,\n      …\n  }";
+    assert_eq!(stringify_diff(&config(80), &config(8080), &styles).unwrap(), expected);
+}
+
+#[test]
+fn diff_of_equal_values_is_empty() {
+    let styles = Styles::default();
+    assert_eq!(stringify_diff(&config(80), &config(80), &styles).unwrap(), "");
+}
+
+#[test]
+fn diff_elides_with_the_elision_of_the_options() {
+    let styles = Styles::default().with_options(Options::default().with_elision(Elision::Text("...".into())));
+    let expected = "  BTreeMap {\n-     port : 80,\n+     port : 8080,\n      ...\n  }";
+    assert_eq!(stringify_diff(&config(80), &config(8080), &styles).unwrap(), expected);
+}
+
+#[test]
+fn diff_matches_fields_by_name() {
+    let a = record(&[("a", "1"), ("b", "2"), ("c", "3")]);
+    let b = record(&[("c", "3"), ("d", "4"), ("b", "5")]);
+    let expected = "  Record {
+-     a=1,
+-     b=2,
++     b=5,
+      …
++     d=4,
+  }";
+    assert_eq!(stringify_diff(&a, &b, &Styles::default()).unwrap(), expected);
+}
+
+#[test]
+fn delta() {
+    let styles = Styles::default();
+    let expected = "BTreeMap {\n    port : 8080,\n    …,\n}";
+    assert_eq!(stringify_delta(&config(8080), &config(80), &styles).unwrap(), expected);
+}
+
+#[test]
+fn delta_writes_new_members_and_leaves_out_removed_ones() {
+    let a = record(&[("a", "1"), ("b", "2"), ("c", "3")]);
+    let b = record(&[("c", "3"), ("d", "4"), ("b", "5")]);
+    let expected = "Record {\n    a=1,\n    b=2,\n    …,\n}";
+    assert_eq!(stringify_delta(&a, &b, &Styles::default()).unwrap(), expected);
+}
+
+#[test]
+fn delta_of_equal_values_is_the_elision() {
+    let styles = Styles::default().with_options(Options::default().with_elision(Elision::Text("...".into())));
+    assert_eq!(stringify_delta(&config(80), &config(80), &styles).unwrap(), "...");
+}
@@ -0,0 +1,71 @@
+use stringify::{Doc, Layout, Options, Role, Stringifier, Theme};
+
+/// A list like `[1, 2, 3]`, which breaks after every element if it doesn't fit.
+fn list(len: usize) -> Doc {
+    let items = (1 ..= len).map(|item| Doc::text(item.to_string()));
+    Doc::group(
+        Doc::text("[")
+            .append(Doc::nest(1, Doc::softline().append(Doc::join(items, Doc::text(",").append(Doc::line())))))
+            .append(Doc::softline())
+            .append(Doc::text("]"))
+    )
+}
+
+fn stringifier(layout: Layout) -> Stringifier {
+    Stringifier::default().with_options(Options::default().with_layout(layout))
+}
+
+#[test]
+fn groups_are_flat_if_they_fit() {
+    assert_eq!(list(4).pretty(12).unwrap(), "[1, 2, 3, 4]");
+    assert_eq!(list(4).pretty(11).unwrap(), "[\n    1,\n    2,\n    3,\n    4\n]");
+}
+
+#[test]
+fn groups_break_from_the_outside_in() {
+    let call = Doc::group(
+        Doc::text("f(")
+            .append(Doc::nest(1, Doc::softline().append(list(3)).append(Doc::text(",")).append(Doc::line()).append(list(8))))
+            .append(Doc::softline())
+            .append(Doc::text(")"))
+    );
+    assert_eq!(call.pretty(40).unwrap(), "f([1, 2, 3], [1, 2, 3, 4, 5, 6, 7, 8])");
+    let expected = "f(
+    [1, 2, 3],
+    [
+        1,
+        2,
+        3,
+        4,
+        5,
+        6,
+        7,
+        8
+    ]
+)";
+    assert_eq!(call.pretty(20).unwrap(), expected);
+}
+
+#[test]
+fn docs_are_groups_themselves() {
+    let doc = Doc::concat(vec![Doc::text("a"), Doc::line(), Doc::text("b")]);
+    assert_eq!(doc.pretty(3).unwrap(), "a b");
+    assert_eq!(doc.pretty(2).unwrap(), "a\nb");
+    assert_eq!(stringifier(Layout::Expanded).to_string(&doc).unwrap(), "a\nb");
+    assert_eq!(Doc::nil().pretty(10).unwrap(), "");
+}
+
+#[test]
+fn docs_follow_the_layout() {
+    assert_eq!(stringifier(Layout::Compact).to_string(&list(3)).unwrap(), "[1, 2, 3]");
+    assert_eq!(stringifier(Layout::Expanded).to_string(&list(3)).unwrap(), "[\n    1,\n    2,\n    3\n]");
+    let expected = "Vec [\n    [\n        1,\n        2,\n        3\n    ],\n]";
+    assert_eq!(stringifier(Layout::Fit { width: 12 }).to_string(&vec![list(3)]).unwrap(), expected);
+    assert_eq!(stringifier(Layout::Fit { width: 14 }).to_string(&vec![list(3)]).unwrap(), "Vec [\n    [1, 2, 3],\n]");
+}
+
+#[test]
+fn styled_text() {
+    let stringifier = Stringifier::default().with_theme(Theme::dark());
+    assert_eq!(stringifier.to_string(&Doc::styled("x", Role::Number)).unwrap(), "\x1b[93mx\x1b[0m");
+}
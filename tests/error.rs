@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, Write};
+use stringify::{Options, Stringifier, Stringify2, StringifyContext, StringifyError, StringifyResult, Styles};
+
+/// A value whose stringification is not valid UTF-8.
+struct Bytes;
+
+impl Stringify2 for Bytes {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    _styles: &Styles,
+                    _ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        buf.write_all(&[0xff])?;
+        Ok(())
+    }
+}
+
+/// A sink whose writes fail with `kind`.
+struct Failing(io::ErrorKind);
+
+impl Write for Failing {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(self.0, "disk is full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_error(kind: io::ErrorKind) -> StringifyError {
+    Stringifier::default().write(&vec![1, 2, 3], &mut Failing(kind)).unwrap_err()
+}
+
+#[test]
+fn io_errors() {
+    let err = write_error(io::ErrorKind::StorageFull);
+    match &err {
+        StringifyError::IoError(io_err) => assert_eq!(io_err.kind(), io::ErrorKind::StorageFull),
+        err => panic!("unexpected error: {:?}", err),
+    }
+    assert_eq!(err.to_string(), "writing the stringification failed: disk is full");
+    let source = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+    assert_eq!(source.kind(), io::ErrorKind::StorageFull);
+}
+
+#[test]
+fn errors_are_compared_by_kind_and_message() {
+    assert_eq!(write_error(io::ErrorKind::Other), write_error(io::ErrorKind::Other));
+    assert_ne!(write_error(io::ErrorKind::Other), write_error(io::ErrorKind::BrokenPipe));
+    let err = write_error(io::ErrorKind::Other);
+    assert_eq!(err.clone(), err);
+}
+
+#[test]
+fn errors_can_be_boxed() {
+    let err: Box<dyn Error + Send + Sync> = Box::new(StringifyError::StyleNotFound { name: "end" });
+    assert_eq!(err.to_string(), "style not found: end");
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn display() {
+    let errors = [
+        (StringifyError::StyleNotFound { name: "end" }, "style not found: end"),
+        (StringifyError::InvalidStylesheet { message: "expected a table".to_string() }, "invalid stylesheet: expected a table"),
+        (StringifyError::InvalidSelector { selector: "a..b".to_string() }, "invalid selector: a..b"),
+    ];
+    for (err, message) in &errors {
+        assert_eq!(&err.to_string(), message);
+        assert!(err.source().is_none());
+    }
+}
+
+#[test]
+fn depth_limit_exceeded() {
+    let mut map = BTreeMap::new();
+    map.insert("a", vec![vec![1u8]]);
+    let options = Options::default().with_depth_limit(Some(2));
+    let err = Stringifier::default().with_options(options).to_string(&vec![map]).unwrap_err();
+    assert_eq!(err, StringifyError::DepthLimitExceeded { limit: 2, path: "[0][a][0]".to_string() });
+    assert_eq!(err.path(), Some("[0][a][0]"));
+    assert_eq!(err.to_string(), "depth limit of 2 exceeded at [0][a][0]");
+}
+
+#[test]
+fn invalid_utf8() {
+    let err = Stringifier::default().to_string(&(1u8, Bytes)).unwrap_err();
+    assert_eq!(err, StringifyError::InvalidUtf8 { path: "[1]".to_string() });
+    assert_eq!(err.to_string(), "invalid UTF-8 in the stringification of [1]");
+    let err = Stringifier::default().to_string(&Bytes).unwrap_err();
+    assert_eq!(err.path(), Some(""));
+    assert_eq!(err.to_string(), "invalid UTF-8 in the stringification of the root value");
+}
+
+#[test]
+fn errors_without_a_path() {
+    assert_eq!(write_error(io::ErrorKind::Other).path(), None);
+    assert_eq!(StringifyError::StyleNotFound { name: "end" }.path(), None);
+}
@@ -0,0 +1,84 @@
+use std::error::Error;
+use std::fmt;
+use stringify::{Layout, Node, Options, Stringifier, Styles};
+
+/// An error that was caused by the error that it wraps, if any.
+#[derive(Debug)]
+struct Layer(&'static str, Option<Box<Layer>>);
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Error for Layer {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.1.as_deref().map(|layer| layer as &(dyn Error + 'static))
+    }
+}
+
+fn layers() -> Layer {
+    let denied = Layer("permission denied", None);
+    let reading = Layer("reading app.toml failed", Some(Box::new(denied)));
+    Layer("loading config failed", Some(Box::new(reading)))
+}
+
+fn compact() -> Stringifier {
+    Stringifier::default().with_options(Options::default().with_layout(Layout::Compact))
+}
+
+#[test]
+fn error_chains() {
+    let error: &dyn Error = &layers();
+    let expected = "loading config failed
+    caused by: reading app.toml failed
+        caused by: permission denied";
+    assert_eq!(Stringifier::default().to_string(error).unwrap(), expected);
+    let expected = "loading config failed: reading app.toml failed: permission denied";
+    assert_eq!(compact().to_string(error).unwrap(), expected);
+}
+
+#[test]
+fn error_chains_in_containers() {
+    let error: &dyn Error = &layers();
+    let expected = "Vec [
+    loading config failed
+        caused by: reading app.toml failed
+            caused by: permission denied,
+]";
+    assert_eq!(Stringifier::default().to_string(&vec![error]).unwrap(), expected);
+}
+
+#[test]
+fn error_chain_nodes() {
+    let error: &dyn Error = &layers();
+    let node = Node::of(error, &Styles::default()).unwrap();
+    let messages = ["loading config failed", "reading app.toml failed", "permission denied"];
+    let items = messages.iter().map(|message| Node::String(message.to_string())).collect();
+    assert_eq!(node, Node::Seq { label: "Error".into(), items });
+}
+
+#[cfg(feature = "anyhow")]
+#[test]
+fn anyhow_errors() {
+    let error = anyhow::Error::new(Layer("permission denied", None)).context("reading app.toml failed");
+    let output = Stringifier::default().to_string(&error).unwrap();
+    assert!(output.starts_with("reading app.toml failed\n    caused by: permission denied"), "{}", output);
+    let expected = "reading app.toml failed: permission denied";
+    assert_eq!(compact().to_string(&error).unwrap(), expected);
+}
+
+#[cfg(feature = "eyre")]
+#[test]
+fn eyre_reports() {
+    let report = eyre::Report::new(Layer("permission denied", None)).wrap_err("reading app.toml failed");
+    let output = Stringifier::default().to_string(&report).unwrap();
+    let expected = "reading app.toml failed
+    caused by: permission denied
+    Location:
+        tests/error_chain.rs:";
+    assert!(output.starts_with(expected), "{}", output);
+    let expected = "reading app.toml failed: permission denied";
+    assert_eq!(compact().to_string(&report).unwrap(), expected);
+}
@@ -0,0 +1,33 @@
+use stringify::{Formatters, Options, Stringifier};
+
+fn hex() -> Stringifier {
+    Stringifier::default().with_formatter(|value: &u8| format!("{:#x}", value))
+}
+
+#[test]
+fn formatters_replace_the_impl_of_their_type() {
+    let expected = "(\n    Vec [\n        0x1,\n        0xff,\n    ],\n    3,\n)";
+    assert_eq!(hex().to_string(&(vec![1u8, 255], 3u16)).unwrap(), expected);
+    assert_eq!(hex().to_string(&vec![Some(1u8)]).unwrap(), "Vec [\n    Some(0x1),\n]");
+}
+
+#[test]
+fn formatters_are_registered_per_type() {
+    let stringifier = hex()
+        .with_formatter(|value: &u8| format!("<{}>", value))
+        .with_formatter(|value: &String| value.to_uppercase());
+    let expected = "(\n    <1>,\n    AB,\n    cd,\n)";
+    assert_eq!(stringifier.to_string(&(1u8, "ab".to_string(), "cd")).unwrap(), expected);
+}
+
+#[test]
+fn registry() {
+    let mut formatters = Formatters::default();
+    assert!(formatters.is_empty());
+    formatters.register(|value: &bool| if *value { "yes" } else { "no" }.to_string());
+    assert!(!formatters.is_empty());
+    assert_eq!(formatters.format(&true), Some("yes".to_string()));
+    assert_eq!(formatters.format(&1u8), None);
+    let options = Options::default().with_formatter(|value: &bool| if *value { "yes" } else { "no" }.to_string());
+    assert_eq!(Stringifier::default().with_options(options).to_string(&false).unwrap(), "no");
+}
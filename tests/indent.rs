@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+use std::io::Write;
+use stringify::{
+    dedent, Dedented, Guides, IndentFn, IndentPolicy, IndentWriter, Layout, Newline, Options, Stringifier, Stringify2,
+    StringifyContext, StringifyResult, StyleKey, Styles,
+};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where T: Stringify2 + ?Sized {
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+fn nested() -> Vec<Vec<Vec<u8>>> {
+    vec![vec![vec![1]]]
+}
+
+#[test]
+fn uniform() {
+    let expected = "Vec [\n    Vec [\n        Vec [\n            1,\n        ],\n    ],\n]";
+    assert_eq!(stringify(&nested(), Options::default()), expected);
+    let options = Options::default().with_indent(IndentPolicy::PerLevel(vec![]));
+    assert_eq!(stringify(&nested(), options), expected);
+}
+
+#[test]
+fn per_level() {
+    let options = Options::default().with_indent(IndentPolicy::PerLevel(vec!["  ".into(), "    ".into()]));
+    let expected = "Vec [\n  Vec [\n      Vec [\n          1,\n      ],\n  ],\n]";
+    assert_eq!(stringify(&nested(), options), expected);
+}
+
+#[test]
+fn custom() {
+    let unit = IndentFn::new(|level| Cow::Owned("-".repeat(level)));
+    let options = Options::default().with_indent(IndentPolicy::Custom(unit));
+    assert_eq!(stringify(&nested(), options), "Vec [\n-Vec [\n---Vec [\n------1,\n---],\n-],\n]");
+}
+
+#[test]
+fn guides() {
+    let options = Options::default().with_guides(Guides::Rail(Guides::RAIL));
+    let expected = "Vec [
+│   Vec [
+│   │   Vec [
+│   │   │   1,
+│   │   ],
+│   ],
+]";
+    assert_eq!(stringify(&nested(), options), expected);
+}
+
+#[test]
+fn guides_follow_the_indentation_policy() {
+    let options = Options::default()
+        .with_guides(Guides::Rail('|'))
+        .with_indent(IndentPolicy::PerLevel(vec!["  ".into()]));
+    assert_eq!(stringify(&nested(), options), "Vec [\n| Vec [\n| | Vec [\n| | | 1,\n| | ],\n| ],\n]");
+}
+
+#[test]
+fn guides_are_left_out_of_compact_output() {
+    let options = Options::default().with_guides(Guides::Rail('|')).with_layout(Layout::Compact);
+    assert_eq!(stringify(&nested(), options), "Vec [Vec [Vec [1]]]");
+}
+
+#[test]
+fn indent_writer() {
+    let mut out = IndentWriter::new(vec![]);
+    writeln!(out, "fn main() {{").unwrap();
+    {
+        let mut body = out.push();
+        writeln!(body, "let a = 1;\n").unwrap();
+        {
+            let mut inner = body.push();
+            assert_eq!(inner.level(), 2);
+            writeln!(inner, "a;").unwrap();
+        }
+        writeln!(body, "b;").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    assert_eq!(out.level(), 0);
+    let expected = "fn main() {\n    let a = 1;\n\n        a;\n    b;\n}\n";
+    assert_eq!(String::from_utf8(out.into_inner()).unwrap(), expected);
+}
+
+#[test]
+fn indent_writers_follow_the_options() {
+    let options = Options::default()
+        .with_guides(Guides::Rail('|'))
+        .with_indent(IndentPolicy::PerLevel(vec!["  ".into()]));
+    let mut out = IndentWriter::with_styles(vec![], Styles::default().with_options(options));
+    {
+        let mut body = out.push();
+        write!(body, "a").unwrap();
+        write!(body, "b\nc").unwrap();
+        body.push().write_all(b"\nd\n").unwrap();
+    }
+    assert_eq!(String::from_utf8(out.into_inner()).unwrap(), "| ab\n| c\n| | d\n");
+}
+
+/// A value with a detail line that is indented one level deeper than it.
+struct Note;
+
+impl Stringify2 for Note {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        let mut line = styles.get_or_default(StyleKey::End);
+        line.newline = Newline::Add;
+        line.indent_level = 0;
+        let depth = ctx.depth();
+        buf.write_all(b"note")?;
+        {
+            let ctx = ctx.indented();
+            assert_eq!(ctx.depth(), depth + 1);
+            self.indent(buf, styles, &ctx, &line)?;
+            buf.write_all(b"detail")?;
+        }
+        assert_eq!(ctx.depth(), depth);
+        Ok(())
+    }
+}
+
+#[test]
+fn indent_guards_restore_the_depth() {
+    let expected = "Vec [\n    note\n        detail,\n    note\n        detail,\n]";
+    assert_eq!(stringify(&vec![Note, Note], Options::default()), expected);
+    let options = Options::default().with_guides(Guides::Rail('|'));
+    assert_eq!(stringify(&Note, options), "note\n|   detail");
+}
+
+#[test]
+fn dedent_strips_common_indentation() {
+    assert_eq!(dedent("    a\n      b\n\n    c\n"), "a\n  b\n\nc");
+    let query = dedent("
+        SELECT *
+        FROM users
+    ");
+    assert_eq!(query, "SELECT *\nFROM users");
+}
+
+#[test]
+fn dedent_borrows_text_without_indentation() {
+    assert!(matches!(dedent("plain"), Cow::Borrowed("plain")));
+}
+
+#[test]
+fn dedented() {
+    let values = vec![Dedented("  x\n    y")];
+    assert_eq!(stringify(&values, Options::default()), "Vec [\n    x\n      y,\n]");
+    assert_eq!(stringify(&values, Options::default().with_layout(Layout::Compact)), "Vec [x y]");
+}
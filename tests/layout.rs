@@ -0,0 +1,115 @@
+use stringify::{Elision, Layout, Options, Stringifier, Stringify2};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where T: Stringify2 + ?Sized {
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+fn nested() -> Vec<Vec<u8>> {
+    vec![vec![1, 2], vec![3, 4, 5]]
+}
+
+const EXPANDED: &str = "Vec [
+    Vec [
+        1,
+        2,
+    ],
+    Vec [
+        3,
+        4,
+        5,
+    ],
+]";
+
+#[test]
+fn expanded() {
+    assert_eq!(stringify(&nested(), Options::default()), EXPANDED);
+}
+
+#[test]
+fn max_depth() {
+    let options = Options::default().with_max_depth(Some(1));
+    let expected = "Vec [
+    Vec [
+        …,
+        …,
+    ],
+    Vec [
+        …,
+        …,
+        …,
+    ],
+]";
+    assert_eq!(stringify(&nested(), options), expected);
+}
+
+#[test]
+fn max_depth_writes_the_elision() {
+    let options = Options::default()
+        .with_max_depth(Some(0))
+        .with_elision(Elision::Text("<elided>".into()));
+    assert_eq!(stringify(&nested(), options), "Vec [\n    <elided>,\n    <elided>,\n]");
+}
+
+#[test]
+fn max_elements() {
+    let options = Options::default().with_max_elements(Some(1));
+    let expected = "Vec [
+    Vec [
+        1,
+        … (1 more)
+    ],
+    … (1 more)
+]";
+    assert_eq!(stringify(&nested(), options), expected);
+}
+
+#[test]
+fn max_elements_of_zero() {
+    let options = Options::default().with_max_elements(Some(0));
+    assert_eq!(stringify(&nested(), options), "Vec [\n    … (2 more)\n]");
+}
+
+#[test]
+fn max_bytes() {
+    let options = Options::default().with_max_bytes(Some(12));
+    assert_eq!(stringify(&nested(), options), "Vec [\n    … (truncated)");
+    let options = Options::default().with_max_bytes(Some(1000));
+    assert_eq!(stringify(&nested(), options), EXPANDED);
+}
+
+#[test]
+fn max_bytes_never_splits_a_character() {
+    let values = vec!["ééééééééééé".to_string()];
+    let options = Options::default().with_max_bytes(Some(15));
+    assert_eq!(stringify(&values, options), "Vec [\n    … (truncated)");
+}
+
+#[test]
+fn compact() {
+    let options = Options::default().with_layout(Layout::Compact);
+    assert_eq!(stringify(&nested(), options), "Vec [Vec [1, 2], Vec [3, 4, 5]]");
+}
+
+#[test]
+fn compact_empty_containers() {
+    let options = Options::default().with_layout(Layout::Compact);
+    assert_eq!(stringify(&vec![Vec::<u8>::new()], options), "Vec [Vec []]");
+}
+
+#[test]
+fn fit() {
+    let fit = |width| Options::default().with_layout(Layout::Fit { width });
+    assert_eq!(stringify(&nested(), fit(40)), "Vec [Vec [1, 2], Vec [3, 4, 5]]");
+    assert_eq!(stringify(&nested(), fit(20)), "Vec [\n    Vec [1, 2],\n    Vec [3, 4, 5],\n]");
+    assert_eq!(stringify(&nested(), fit(5)), EXPANDED);
+}
+
+#[test]
+fn fit_breaks_only_the_values_that_do_not_fit() {
+    let fit = |width| Options::default().with_layout(Layout::Fit { width });
+    let expected = "Vec [\n    Vec [1, 2],\n    Vec [\n        3,\n        4,\n        5,\n    ],\n]";
+    assert_eq!(stringify(&nested(), fit(16)), expected);
+    assert_eq!(stringify(&nested(), fit(17)), "Vec [\n    Vec [1, 2],\n    Vec [3, 4, 5],\n]");
+    assert_eq!(stringify(&nested(), fit(31)), "Vec [Vec [1, 2], Vec [3, 4, 5]]");
+}
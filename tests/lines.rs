@@ -0,0 +1,58 @@
+use stringify::{Layout, LinePrefix, Options, PrefixFn, Stringifier, Stringify2};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where
+    T: Stringify2 + ?Sized,
+{
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+fn nested() -> Vec<Vec<u8>> {
+    vec![vec![1], vec![]]
+}
+
+#[test]
+fn text_prefixes() {
+    let options = Options::default().with_line_prefix(LinePrefix::Text("> ".into()));
+    let expected = "> Vec [\n>     Vec [\n>         1,\n>     ],\n>     Vec [],\n> ]";
+    assert_eq!(stringify(&nested(), options.clone()), expected);
+    assert_eq!(stringify(&nested(), options.with_layout(Layout::Compact)), "> Vec [Vec [1], Vec []]");
+}
+
+#[test]
+fn custom_prefixes_see_the_number_and_level_of_each_line() {
+    let prefix = PrefixFn::new(|line, level| format!("{}/{} ", line, level).into());
+    let options = Options::default().with_line_prefix(LinePrefix::Custom(prefix));
+    let expected = "1/0 Vec [\n2/1     Vec [\n3/2         1,\n4/1     ],\n5/1     Vec [],\n6/0 ]";
+    assert_eq!(stringify(&nested(), options), expected);
+}
+
+#[test]
+fn prefixes_of_multiline_strings() {
+    let options = Options::default().with_line_prefix(LinePrefix::Text("# ".into()));
+    assert_eq!(stringify("a\nb", options), "# a\n# b");
+}
+
+#[test]
+fn line_numbers() {
+    let options = Options::default().with_line_numbers(true);
+    let expected = "1 | Vec [\n2 |     Vec [\n3 |         1,\n4 |     ],\n5 |     Vec [],\n6 | ]";
+    assert_eq!(stringify(&nested(), options), expected);
+}
+
+#[test]
+fn line_number_gutters_are_as_wide_as_the_last_number() {
+    let options = Options::default().with_line_numbers(true);
+    let output = stringify(&vec![0; 9], options);
+    assert!(output.starts_with(" 1 | Vec [\n 2 |     0,\n"));
+    assert!(output.ends_with("\n10 |     0,\n11 | ]"));
+}
+
+#[test]
+fn line_numbers_come_before_prefixes() {
+    let options = Options::default()
+        .with_line_numbers(true)
+        .with_line_prefix(LinePrefix::Text("> ".into()));
+    assert_eq!(stringify("a\n\nb", options), "1 | > a\n2 | > \n3 | > b");
+    assert_eq!(stringify("a\n\nb", Options::default().with_line_numbers(true)), "1 | a\n2 |\n3 | b");
+}
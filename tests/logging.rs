@@ -0,0 +1,52 @@
+#![cfg(feature = "log")]
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+use stringify::{log_stringified, record_prefix, Layout, Options, Prefixed, Stringifier};
+
+/// A logger that keeps the messages of the records that it is given.
+struct Capture(Mutex<Vec<String>>);
+
+impl Log for Capture {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(format!("{} {}", record.level(), record.args()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Capture = Capture(Mutex::new(vec![]));
+
+#[test]
+fn prefixed() {
+    let styles = Stringifier::default().styles().clone();
+    let values = vec![1u8, 2];
+    assert_eq!(Prefixed::new(&values, &styles, "  | ").to_string(), "Vec [\n  |     1,\n  |     2,\n  | ]");
+    assert_eq!(Prefixed::new(&3u8, &styles, "  | ").to_string(), "3");
+}
+
+#[test]
+fn record_prefixes() {
+    assert_eq!(record_prefix(Level::Info, "db"), "INFO  db | ");
+    assert_eq!(record_prefix(Level::Error, "db::pool"), "ERROR db::pool | ");
+}
+
+/// The only test that logs, since there is a single logger per process.
+#[test]
+fn log_stringified() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+    let values = vec![1u8, 2];
+    log_stringified!(target: "db", Level::Info, &values);
+    log_stringified!(target: "db", Level::Debug, &values);
+    let compact = Stringifier::default().with_options(Options::default().with_layout(Layout::Compact));
+    log_stringified!(target: "db", Level::Warn, &values, compact.styles());
+    let messages = LOGGER.0.lock().unwrap().clone();
+    assert_eq!(messages, ["INFO Vec [\nINFO  db |     1,\nINFO  db |     2,\nINFO  db | ]", "WARN Vec [1, 2]"]);
+}
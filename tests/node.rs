@@ -0,0 +1,50 @@
+use std::collections::{BTreeSet, HashSet};
+use stringify::{stringify_delta, stringify_diff, Node, Options, Stringifier, Stringify2, Styles};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where T: Stringify2 + ?Sized {
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+fn set(items: &[u8]) -> BTreeSet<u8> {
+    items.iter().copied().collect()
+}
+
+#[test]
+fn nodes_are_written_like_their_values() {
+    let values = (vec![Some(1), None], set(&[1, 2]), "text");
+    let node = Node::of(&values, &Styles::default()).unwrap();
+    assert_eq!(stringify(&node, Options::default()), stringify(&values, Options::default()));
+}
+
+#[test]
+fn sets() {
+    let node = Node::of(&set(&[1, 2]), &Styles::default()).unwrap();
+    let items = vec![Node::Number("1".to_string()), Node::Number("2".to_string())];
+    assert_eq!(node, Node::Set { label: "BTreeSet".into(), items });
+    assert_eq!(stringify(&node, Options::default()), "BTreeSet {\n    1,\n    2,\n}");
+    let hash_set: HashSet<u8> = [2, 1].iter().copied().collect();
+    let node = Node::of(&hash_set, &Styles::default().with_options(Options::canonical())).unwrap();
+    assert!(matches!(node, Node::Set { .. }));
+}
+
+#[test]
+fn sets_are_not_rows_of_a_grid() {
+    let options = Options::default().with_grid(true);
+    assert_eq!(stringify(&vec![set(&[1, 2])], options.clone()), "Vec [
+    BTreeSet {
+        1,
+        2,
+    },
+]");
+    assert_eq!(stringify(&vec![vec![1, 2]], options), "Vec [\n    Vec [1, 2],\n]");
+}
+
+#[test]
+fn sets_are_diffed_by_value() {
+    let styles = Styles::default();
+    let diff = stringify_diff(&set(&[1, 2, 3]), &set(&[0, 2, 3, 4]), &styles).unwrap();
+    assert_eq!(diff, "  BTreeSet {\n-     1,\n      …\n+     0,\n+     4,\n  }");
+    let delta = stringify_delta(&set(&[0, 2, 3, 4]), &set(&[1, 2, 3]), &styles).unwrap();
+    assert_eq!(delta, "BTreeSet {\n    0,\n    …,\n    4,\n}");
+}
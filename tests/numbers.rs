@@ -0,0 +1,50 @@
+use stringify::{Grouping, NumberFormat, Options, Radix, Stringifier, Stringify2};
+
+fn stringify<T>(value: &T, number: NumberFormat) -> String
+where T: Stringify2 + ?Sized {
+    let options = Options::default().with_number(number);
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+#[test]
+fn radix() {
+    let number = NumberFormat::default();
+    assert_eq!(stringify(&42u32, number), "42");
+    assert_eq!(stringify(&vec![42u8, 255], number.with_radix(Radix::Hexadecimal)), "Vec [\n    0x2a,\n    0xff,\n]");
+    assert_eq!(stringify(&42u32, number.with_radix(Radix::Octal)), "0o52");
+    assert_eq!(stringify(&42u64, number.with_radix(Radix::Binary)), "0b101010");
+}
+
+#[test]
+fn radix_writes_negative_integers_in_twos_complement() {
+    let number = NumberFormat::default();
+    assert_eq!(stringify(&-5i8, number.with_radix(Radix::Binary)), "0b11111011");
+    assert_eq!(stringify(&-255i64, number.with_radix(Radix::Hexadecimal)), "0xffffffffffffff01");
+}
+
+#[test]
+fn radix_leaves_floats_alone() {
+    assert_eq!(stringify(&1.5f64, NumberFormat::default().with_radix(Radix::Hexadecimal)), "1.5");
+}
+
+#[test]
+fn grouping() {
+    let number = NumberFormat::default();
+    assert_eq!(stringify(&-1234567i64, number.with_grouping(Grouping::Separator('_'))), "-1_234_567");
+    assert_eq!(stringify(&999u16, number.with_grouping(Grouping::Separator(','))), "999");
+    let max = "340,282,366,920,938,463,463,374,607,431,768,211,455";
+    assert_eq!(stringify(&u128::MAX, number.with_grouping(Grouping::Separator(','))), max);
+}
+
+#[test]
+fn grouping_follows_the_radix() {
+    let number = NumberFormat::default().with_grouping(Grouping::Separator('_'));
+    assert_eq!(stringify(&0x12345u32, number.with_radix(Radix::Hexadecimal)), "0x1_2345");
+    assert_eq!(stringify(&0b1010_1010u8, number.with_radix(Radix::Binary)), "0b1010_1010");
+    assert_eq!(stringify(&0o1234567u32, number.with_radix(Radix::Octal)), "0o1_234_567");
+}
+
+#[test]
+fn grouping_leaves_floats_alone() {
+    assert_eq!(stringify(&1234.5f64, NumberFormat::default().with_grouping(Grouping::Separator(','))), "1234.5");
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "rayon")]
+
+use stringify::{Guides, LinePrefix, Multiline, Options, Parallel, PrefixFn, Stringifier, Stringify2};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where
+    T: Stringify2 + ?Sized,
+{
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+/// More rows than `Parallel` needs to split them into several chunks.
+fn rows() -> Vec<Vec<u32>> {
+    (0 .. 5000).map(|idx| vec![idx, idx + 1]).collect()
+}
+
+#[test]
+fn parallel_output_is_that_of_the_slice() {
+    let rows = rows();
+    let options = vec![
+        Options::default(),
+        Options::default().with_indices(true),
+        Options::default().with_sizes(true),
+        Options::default().with_max_elements(Some(4500)),
+        Options::default().with_max_depth(Some(1)),
+        Options::default().with_guides(Guides::Rail('|')),
+        Options::canonical(),
+    ];
+    for options in options {
+        assert_eq!(stringify(&Parallel(&rows), options.clone()), stringify(&rows, options));
+    }
+}
+
+#[test]
+fn nested_parallel_output_keeps_its_depth() {
+    let rows = rows();
+    assert_eq!(
+        stringify(&Some(Parallel(&rows)), Options::default()),
+        stringify(&Some(rows.clone()), Options::default()),
+    );
+    assert_eq!(
+        stringify(&Some(vec![Parallel(&rows)]), Options::default()),
+        stringify(&Some(vec![rows.clone()]), Options::default()),
+    );
+}
+
+#[test]
+fn line_prefixes_see_the_level_of_each_line() {
+    let rows = rows();
+    let options = vec![
+        Options::default().with_line_prefix(LinePrefix::Text("> ".into())),
+        Options::default().with_line_prefix(LinePrefix::Custom(PrefixFn::new(|number, level| {
+            format!("{}:{} ", number, level).into()
+        }))),
+        Options::default().with_line_numbers(true),
+    ];
+    for options in options {
+        assert_eq!(
+            stringify(&Some(Parallel(&rows)), options.clone()),
+            stringify(&Some(rows.clone()), options),
+        );
+    }
+}
+
+#[test]
+fn margin_strings_are_written_as_in_the_slice() {
+    let rows: Vec<String> = (0 .. 5000).map(|idx| format!("line {}\nline {}", idx, idx + 1)).collect();
+    let options = Options::default().with_multiline(Multiline::Margin);
+    assert_eq!(stringify(&Parallel(&rows), options.clone()), stringify(&rows, options));
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+use stringify::{BufferPool, Stringifier};
+
+#[test]
+fn buffers_are_reused() {
+    let pool = BufferPool::default();
+    assert!(pool.is_empty());
+    let mut buf = pool.take();
+    buf.push_str("text");
+    let capacity = buf.capacity();
+    pool.give(buf);
+    assert_eq!(pool.len(), 1);
+    let buf = pool.take();
+    assert_eq!(buf, "");
+    assert_eq!(buf.capacity(), capacity);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn pools_are_bounded() {
+    let pool = BufferPool::new(1, 16);
+    pool.give(String::with_capacity(8));
+    pool.give(String::with_capacity(8));
+    assert_eq!(pool.len(), 1);
+    let pool = BufferPool::new(4, 16);
+    pool.give(String::with_capacity(32));
+    pool.give(String::new());
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn stringifiers_share_their_pool() {
+    let pool = Arc::new(BufferPool::default());
+    let stringifier = Stringifier::default().with_pool(pool.clone());
+    let mut buf = stringifier.take_buffer();
+    buf.push_str("stale");
+    let text = stringifier.to_string_in(&vec![1u8], buf).unwrap();
+    assert_eq!(text, "Vec [\n    1,\n]");
+    stringifier.return_buffer(text);
+    assert_eq!(pool.len(), 1);
+    assert!(Arc::ptr_eq(stringifier.pool(), &pool));
+}
@@ -0,0 +1,170 @@
+use stringify::{Csv, Dot, Html, Json, Markdown, Node, Render, Ron, Stringifier, Table, Tree, Yaml};
+
+/// Two records, like `Vec [Row {name: "a", n: 1}, Row {name: "b,c", n: 22}]`.
+fn rows() -> Node {
+    let row = |name: &str, n: &str| Node::Struct {
+        label: "Row".into(),
+        fields: vec![("name".into(), Node::String(name.to_string())), ("n".into(), Node::Number(n.to_string()))],
+    };
+    Node::Seq { label: "Vec".into(), items: vec![row("a", "1"), row("b,c", "22")] }
+}
+
+fn render(renderer: &dyn Render) -> String {
+    renderer.render_to_string(&rows()).unwrap()
+}
+
+#[test]
+fn yaml() {
+    assert_eq!(render(&Yaml::default()), "- name: a\n  n: 1\n- name: \"b,c\"\n  n: 22");
+}
+
+#[test]
+fn yaml_of_a_value() {
+    let value = (Some(1u8), None::<u8>, "a\"b\n".to_string(), true);
+    let yaml = Stringifier::default().render(&value, &Yaml::default()).unwrap();
+    assert_eq!(yaml, "- 1\n- null\n- \"a\\\"b\\n\"\n- true");
+}
+
+#[test]
+fn json() {
+    assert_eq!(render(&Json::compact()), r#"[{"name":"a","n":1},{"name":"b,c","n":22}]"#);
+    let expected = r#"[
+  {
+    "name": "a",
+    "n": 1
+  },
+  {
+    "name": "b,c",
+    "n": 22
+  }
+]"#;
+    assert_eq!(render(&Json::pretty()), expected);
+}
+
+#[test]
+fn stringifier_renders_the_node_of_a_value() {
+    let stringifier = Stringifier::default();
+    assert_eq!(stringifier.to_json(&vec![Some(1), None]).unwrap(), "[1,null]");
+    assert_eq!(stringifier.render(&vec![1, 2], &Json::compact()).unwrap(), "[1,2]");
+}
+
+#[test]
+fn json_escapes_strings() {
+    let value = ("a\"b\n".to_string(), vec![Vec::<u8>::new()]);
+    assert_eq!(Stringifier::default().to_json(&value).unwrap(), r#"["a\"b\n",[[]]]"#);
+    assert_eq!(Stringifier::default().to_json_pretty(&vec![Vec::<u8>::new()]).unwrap(), "[\n  []\n]");
+}
+
+#[test]
+fn ron() {
+    assert_eq!(render(&Ron::compact()), r#"[Row(name:"a",n:1),Row(name:"b,c",n:22)]"#);
+}
+
+#[test]
+fn ron_of_a_value() {
+    let value = (Some(1u8), None::<u8>);
+    assert_eq!(Stringifier::default().to_ron(&value).unwrap(), "(\n    Some(\n        1,\n    ),\n    None,\n)");
+}
+
+#[test]
+fn markdown() {
+    let expected = "- Row
+  - **name**: a
+  - **n**: 1
+- Row
+  - **name**: b,c
+  - **n**: 22";
+    assert_eq!(render(&Markdown::default()), expected);
+}
+
+#[test]
+fn markdown_of_a_value() {
+    let value = (None::<u8>, "a\nb".to_string());
+    let markdown = Stringifier::default().render(&value, &Markdown::default()).unwrap();
+    assert_eq!(markdown, "- null\n- a<br>b");
+}
+
+#[test]
+fn html() {
+    let html = render(&Html::default());
+    assert!(html.starts_with(r#"<details class="stringify-seq" open><summary><span class="stringify-label">Vec</span></summary><ul>"#));
+    assert!(html.contains(r#"<li><span class="stringify-field">name</span>: <span class="stringify-string">&quot;b,c&quot;</span></li>"#));
+    assert!(html.ends_with("</ul></details>"));
+}
+
+#[test]
+fn html_escapes_text() {
+    let html = Stringifier::default().render(&"<a&b>".to_string(), &Html::default()).unwrap();
+    assert_eq!(html, r#"<span class="stringify-string">&quot;&lt;a&amp;b&gt;&quot;</span>"#);
+}
+
+#[test]
+fn dot() {
+    let expected = r#"digraph "stringify" {
+    n0 [shape=box, label="Vec"];
+    n1 [shape=box, label="Row"];
+    n2 [shape=plaintext, label="a"];
+    n1 -> n2 [label="name"];
+    n3 [shape=plaintext, label="1"];
+    n1 -> n3 [label="n"];
+    n0 -> n1 [label="0"];
+    n4 [shape=box, label="Row"];
+    n5 [shape=plaintext, label="b,c"];
+    n4 -> n5 [label="name"];
+    n6 [shape=plaintext, label="22"];
+    n4 -> n6 [label="n"];
+    n0 -> n4 [label="1"];
+}
+"#;
+    assert_eq!(render(&Dot::default()), expected);
+}
+
+#[test]
+fn dot_of_a_value() {
+    let dot = Stringifier::default().render(&vec![1u8], &Dot::default()).unwrap();
+    let expected = r#"digraph "stringify" {
+    n0 [shape=box, label="Vec"];
+    n1 [shape=plaintext, label="1"];
+    n0 -> n1 [label="0"];
+}
+"#;
+    assert_eq!(dot, expected);
+}
+
+#[test]
+fn csv() {
+    assert_eq!(render(&Csv::default()), "name,n\na,1\n\"b,c\",22\n");
+}
+
+#[test]
+fn csv_of_scalars() {
+    assert_eq!(Stringifier::default().to_csv(&vec![1u8, 2]).unwrap(), "value\n1\n2\n");
+}
+
+#[test]
+fn table() {
+    assert_eq!(render(&Table::default()), "name  n\n----  --\na     1\nb,c   22\n");
+}
+
+#[test]
+fn table_of_scalars() {
+    assert_eq!(Stringifier::default().to_table(&vec![1u8, 2]).unwrap(), "value\n-----\n1\n2\n");
+}
+
+#[test]
+fn tree() {
+    let expected = r#"Vec
+├── Row
+│   ├── name: "a"
+│   └── n: 1
+└── Row
+    ├── name: "b,c"
+    └── n: 22"#;
+    assert_eq!(render(&Tree::default()), expected);
+}
+
+#[test]
+fn tree_of_empty_containers() {
+    let tree = Stringifier::default().render(&vec![Vec::<u8>::new()], &Tree::default()).unwrap();
+    assert_eq!(tree, "Vec\n└── Vec []");
+}
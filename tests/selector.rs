@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use stringify::{styles, PathSegment, Selector, Stringifier, Style, StyleKey, StringifyError, Styles};
+
+fn inline(selector: &str) -> Stringifier {
+    let overrides = styles! { StyleKey::End => Style::INLINE };
+    Stringifier::new(Styles::default().with_selector(selector.parse().unwrap(), overrides))
+}
+
+#[test]
+fn parse() {
+    let selector: Selector = "servers[*].port".parse().unwrap();
+    assert_eq!(selector.to_string(), ".servers[*].port");
+    let selector: Selector = ".a.*[3][key]".parse().unwrap();
+    assert_eq!(selector.to_string(), ".a.*[3][key]");
+    for invalid in &["a..b", "a[1", "."] {
+        let err = invalid.parse::<Selector>().unwrap_err();
+        assert_eq!(err, StringifyError::InvalidSelector { selector: invalid.to_string() });
+    }
+}
+
+#[test]
+fn matches() {
+    let path = [PathSegment::Field("servers".into()), PathSegment::Index(2), PathSegment::Field("port".into())];
+    let selector: Selector = "servers[*].port".parse().unwrap();
+    assert!(selector.matches(&path));
+    assert!(!selector.matches(&path[.. 2]));
+    assert!(!"servers[1].port".parse::<Selector>().unwrap().matches(&path));
+    assert!("*[2].*".parse::<Selector>().unwrap().matches(&path));
+    assert!("".parse::<Selector>().unwrap().matches(&[]));
+}
+
+#[test]
+fn selected_values_are_styled() {
+    let values = vec![vec![1u8, 2], vec![3, 4]];
+    let expected = "Vec [\n    Vec [\n        1,\n        2,\n    ],\n    Vec [3,4,],\n]";
+    assert_eq!(inline("[1]").to_string(&values).unwrap(), expected);
+}
+
+#[test]
+fn selectors_match_map_keys() {
+    let mut map = BTreeMap::new();
+    map.insert("a", vec![1u8, 2]);
+    map.insert("b", vec![3u8]);
+    let expected = "BTreeMap {\n    a : Vec [\n        1,\n        2,\n    ],\n    b : Vec [3,],\n}";
+    assert_eq!(inline("[b]").to_string(&map).unwrap(), expected);
+}
+
+#[test]
+fn selected_styles_apply_to_the_whole_subtree() {
+    let values = vec![vec![vec![1u8]]];
+    assert_eq!(inline("[*]").to_string(&values).unwrap(), "Vec [\n    Vec [Vec [1,],],\n]");
+}
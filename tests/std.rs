@@ -0,0 +1,49 @@
+use stringify::{Layout, Options, Stringifier, Stringify2};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where T: Stringify2 + ?Sized {
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+#[test]
+fn option() {
+    assert_eq!(stringify(&Some(1), Options::default()), "Some(1)");
+    assert_eq!(stringify(&None::<u8>, Options::default()), "None");
+    assert_eq!(stringify(&Some(vec![1]), Options::default()), "Some(Vec [\n    1,\n])");
+}
+
+#[test]
+fn option_in_a_container() {
+    let values = vec![Some(vec![1, 2]), None];
+    assert_eq!(stringify(&values, Options::default()), "Vec [
+    Some(Vec [
+        1,
+        2,
+    ]),
+    None,
+]");
+    let compact = Options::default().with_layout(Layout::Compact);
+    assert_eq!(stringify(&values, compact), "Vec [Some(Vec [1, 2]), None]");
+}
+
+
+#[test]
+fn result() {
+    let values: Vec<Result<Vec<u8>, String>> = vec![Ok(vec![1]), Err("oops".to_string())];
+    assert_eq!(stringify(&values, Options::default()), "Vec [
+    Ok(Vec [
+        1,
+    ]),
+    Err(oops),
+]");
+}
+
+#[test]
+fn result_payloads_are_components() {
+    let value: Result<Vec<Vec<u8>>, u8> = Ok(vec![vec![1]]);
+    let options = Options::default().with_max_depth(Some(1)).with_layout(Layout::Compact);
+    assert_eq!(stringify(&value, options), "Ok(Vec [Vec […]])");
+    let stringifier = Stringifier::default().with_formatter(|value: &u8| format!("{:#04x}", value));
+    let value: Result<bool, u8> = Err(255);
+    assert_eq!(stringifier.to_string(&value).unwrap(), "Err(0xff)");
+}
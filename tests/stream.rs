@@ -0,0 +1,104 @@
+use std::io::{self, Write};
+use stringify::{
+    Flush, Layout, Options, StyleKey, Stringifier, Stringify2, StringifyContext, StringifyResult, Styles
+};
+
+/// A type with a hand-written impl that keeps the default `to_node`.
+struct Pair(u8, u8);
+
+impl Stringify2 for Pair {
+    fn stringify<W>(&self,
+                    buf: &mut W,
+                    styles: &Styles,
+                    ctx: &mut StringifyContext) -> StringifyResult<()>
+    where W: Write {
+        write!(buf, "Pair")?;
+        let mut ctx = ctx.indented();
+        self.indent(buf, styles, &ctx, &styles.get_or_default(StyleKey::Name))?;
+        self.0.stringify(buf, styles, &mut ctx)?;
+        write!(buf, " & ")?;
+        self.1.stringify(buf, styles, &mut ctx)?;
+        Ok(())
+    }
+}
+
+/// A sink that records where it was flushed.
+#[derive(Default)]
+struct Sink {
+    bytes: Vec<u8>,
+    flushes: Vec<usize>,
+}
+
+impl Write for Sink {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.bytes.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes.push(self.bytes.len());
+        Ok(())
+    }
+}
+
+fn stream<T>(stringifier: &Stringifier, value: &T) -> Sink
+where T: Stringify2 + ?Sized {
+    let mut sink = Sink::default();
+    stringifier.stream(value, &mut sink).unwrap();
+    sink
+}
+
+fn nested() -> Vec<Vec<u8>> {
+    vec![vec![1], vec![2, 3]]
+}
+
+#[test]
+fn stream_matches_write() {
+    let values = vec![Pair(1, 2), Pair(3, 4)];
+    let options = [
+        Options::default(),
+        Options::default().with_layout(Layout::Compact),
+        Options::default().with_layout(Layout::Fit { width: 12 }),
+        Options::default().with_max_bytes(Some(20)),
+        Options::default().with_line_numbers(true),
+    ];
+    for options in options {
+        let stringifier = Stringifier::default().with_options(options);
+        let mut written = vec![];
+        stringifier.write(&values, &mut written).unwrap();
+        assert_eq!(stream(&stringifier, &values).bytes, written);
+    }
+}
+
+#[test]
+fn flush_per_item() {
+    let stringifier = Stringifier::default().with_flush(Flush::PerItem);
+    let sink = stream(&stringifier, &nested());
+    let output = String::from_utf8(sink.bytes).unwrap();
+    let flushed: Vec<&str> = sink.flushes.iter().map(|&len| &output[.. len]).collect();
+    let expected = [
+        "Vec [\n",
+        "Vec [\n    Vec [\n        1,\n    ],\n",
+        "Vec [\n    Vec [\n        1,\n    ],\n    Vec [\n        2,\n        3,\n    ],\n",
+        "Vec [\n    Vec [\n        1,\n    ],\n    Vec [\n        2,\n        3,\n    ],\n]",
+    ];
+    assert_eq!(flushed, expected);
+}
+
+#[test]
+fn flush_by_bytes() {
+    let stringifier = Stringifier::default().with_flush(Flush::Bytes(16));
+    let sink = stream(&stringifier, &nested());
+    let (last, flushes) = sink.flushes.split_last().unwrap();
+    assert!(flushes.len() > 2);
+    assert!(flushes[0] >= 16);
+    assert!(flushes.windows(2).all(|pair| pair[1] - pair[0] >= 16));
+    assert_eq!(*last, sink.bytes.len());
+}
+
+#[test]
+fn flush_never() {
+    let stringifier = Stringifier::default().with_flush(Flush::Never);
+    let sink = stream(&stringifier, &nested());
+    assert_eq!(sink.flushes, [sink.bytes.len()]);
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "serde")]
+
+#[cfg(any(feature = "toml", feature = "json"))]
+use std::{env, fs, process};
+use stringify::{Styles, Stylesheet};
+#[cfg(any(feature = "toml", feature = "json"))]
+use stringify::{Quoting, StringifyError};
+#[cfg(feature = "toml")]
+use stringify::{Color, Grouping, Role, Stringifier, Theme};
+
+#[cfg(feature = "toml")]
+const TOML: &str = r#"
+[styles.start]
+newline = "Omit"
+indent_level = 0
+indent = "  "
+
+[options]
+quoting = "Quoted"
+number = { grouping = { Separator = "_" } }
+theme = { number = "BrightYellow", string = "BrightGreen" }
+"#;
+
+/// Write `text` to a temporary file with the given `extension`.
+#[cfg(any(feature = "toml", feature = "json"))]
+fn temp_file(name: &str, extension: &str, text: &str) -> std::path::PathBuf {
+    let path = env::temp_dir().join(format!("stringify-{}-{}.{}", name, process::id(), extension));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn default() {
+    let stylesheet = Stylesheet::default();
+    assert_eq!(Styles::from(stylesheet), Styles::default());
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml() {
+    let stylesheet = Stylesheet::from_toml(TOML).unwrap();
+    assert_eq!(stylesheet.options.quoting, Quoting::Quoted);
+    assert_eq!(stylesheet.options.number.grouping, Grouping::Separator('_'));
+    assert_eq!(stylesheet.options.theme.number, Some(Color::BrightYellow));
+    let stringifier = Stringifier::new(stylesheet.into());
+    assert_eq!(stringifier.to_string(&vec![1234567u32]).unwrap(), "Vec [\n    \x1b[93m1_234_567\x1b[0m,\n]");
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn invalid_toml() {
+    let err = Stylesheet::from_toml("[options]\nquoting = 3").unwrap_err();
+    assert!(matches!(err, StringifyError::InvalidStylesheet { .. }), "{:?}", err);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json() {
+    let stylesheet = Stylesheet::from_json(r#"{"options": {"quoting": "Quoted"}}"#).unwrap();
+    assert_eq!(stylesheet.options.quoting, Quoting::Quoted);
+    assert_eq!(stylesheet.styles, Styles::default());
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn themes() {
+    let theme = Theme::from_toml("number = \"Red\"").unwrap();
+    assert_eq!(theme, Theme::plain().with_color(Role::Number, Some(Color::Red)));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn from_path() {
+    let path = temp_file("from-path", "toml", TOML);
+    let styles = Styles::from_path(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(styles.unwrap(), Stylesheet::from_toml(TOML).unwrap().into());
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+#[test]
+fn from_path_of_an_unsupported_format() {
+    let path = temp_file("unsupported", "yaml", "options: {}");
+    let err = Stylesheet::from_path(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+    assert!(matches!(err, StringifyError::InvalidStylesheet { .. }), "{:?}", err);
+}
@@ -0,0 +1,67 @@
+use stringify::{Layout, Multiline, Options, Quoting, Stringifier, Stringify2};
+
+fn stringify<T>(value: &T, options: Options) -> String
+where T: Stringify2 + ?Sized {
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+#[test]
+fn raw() {
+    assert_eq!(stringify(&"a\"b\nc".to_string(), Options::default()), "a\"b\nc");
+    assert_eq!(stringify(&'\'', Options::default()), "'");
+}
+
+#[test]
+fn quoted() {
+    let quoted = Options::default().with_quoting(Quoting::Quoted);
+    let text = "a\"b\n\t\\\u{1}é".to_string();
+    assert_eq!(stringify(&text, quoted.clone()), r#""a\"b\n\t\\\u{1}é""#);
+    assert_eq!(stringify(&vec!["a", "b"], quoted), "Vec [\n    \"a\",\n    \"b\",\n]");
+}
+
+#[test]
+fn quoted_chars_escape_only_their_own_quote() {
+    let quoted = Options::default().with_quoting(Quoting::Quoted);
+    assert_eq!(stringify(&'\'', quoted.clone()), r"'\''");
+    assert_eq!(stringify(&'"', quoted.clone()), r#"'"'"#);
+    assert_eq!(stringify(&"it's".to_string(), quoted), r#""it's""#);
+}
+
+fn multiline(multiline: Multiline) -> Options {
+    Options::default().with_multiline(multiline)
+}
+
+#[test]
+fn verbatim() {
+    let lines = vec!["a\nb".to_string()];
+    assert_eq!(stringify(&lines, multiline(Multiline::Verbatim)), "Vec [\n    a\nb,\n]");
+}
+
+#[test]
+fn indented() {
+    let lines = vec!["a\nb".to_string()];
+    assert_eq!(stringify(&lines, multiline(Multiline::Indented)), "Vec [\n    a\n    b,\n]");
+    assert_eq!(stringify(&"a\nb".to_string(), multiline(Multiline::Indented)), "a\nb");
+}
+
+#[test]
+fn margin() {
+    let lines = vec!["a\nb".to_string()];
+    assert_eq!(stringify(&lines, multiline(Multiline::Margin)), "Vec [\n    | a\n    | b,\n]");
+    assert_eq!(stringify(&"a\nb".to_string(), multiline(Multiline::Margin)), "| a\n| b");
+}
+
+#[test]
+fn flat_blocks_separate_lines_by_a_space() {
+    let lines = vec!["a\nb".to_string()];
+    let compact = |policy| multiline(policy).with_layout(Layout::Compact);
+    assert_eq!(stringify(&lines, compact(Multiline::Indented)), "Vec [a b]");
+    assert_eq!(stringify(&lines, compact(Multiline::Margin)), "Vec [| a | b]");
+}
+
+#[test]
+fn quoted_blocks_are_escaped() {
+    let lines = vec!["a\nb".to_string()];
+    let options = multiline(Multiline::Margin).with_quoting(Quoting::Quoted);
+    assert_eq!(stringify(&lines, options), "Vec [\n    \"a\\nb\",\n]");
+}
@@ -0,0 +1,44 @@
+use std::env;
+use stringify::{Color, Options, Role, Stringifier, Stringify2, Theme};
+
+fn stringify<T>(value: &T, theme: Theme) -> String
+where T: Stringify2 + ?Sized {
+    let options = Options::default().with_theme(theme);
+    Stringifier::default().with_options(options).to_string(value).unwrap()
+}
+
+#[test]
+fn plain() {
+    assert_eq!(stringify(&vec![Some(1.5)], Theme::plain()), "Vec [\n    Some(1.5),\n]");
+}
+
+#[test]
+fn themes_color_each_role() {
+    let expected = "\x1b[96mVec [\x1b[0m
+    \x1b[96mSome(\x1b[0m\x1b[93m1.5\x1b[0m\x1b[96m)\x1b[0m,
+\x1b[96m]\x1b[0m";
+    assert_eq!(stringify(&vec![Some(1.5)], Theme::dark()), expected);
+    let expected = "\x1b[36m(\x1b[0m\n    \x1b[32ma\x1b[0m,\n    \x1b[32mc\x1b[0m,\n\x1b[36m)\x1b[0m";
+    assert_eq!(stringify(&("a".to_string(), 'c'), Theme::light()), expected);
+}
+
+#[test]
+fn roles_without_a_color_are_plain() {
+    let theme = Theme::plain().with_color(Role::Number, Some(Color::Red));
+    assert_eq!(theme.color(Role::Number), Some(Color::Red));
+    assert_eq!(theme.color(Role::String), None);
+    assert_eq!(stringify(&vec![1u8], theme), "Vec [\n    \x1b[31m1\x1b[0m,\n]");
+}
+
+/// The only test that sets `NO_COLOR`, since tests run in parallel.
+#[test]
+fn auto() {
+    env::remove_var("NO_COLOR");
+    assert_eq!(Theme::dark().auto_for(true), Theme::dark());
+    assert_eq!(Theme::dark().auto_for(false), Theme::plain());
+    env::set_var("NO_COLOR", "");
+    assert_eq!(Theme::dark().auto_for(true), Theme::dark());
+    env::set_var("NO_COLOR", "1");
+    assert_eq!(Theme::dark().auto_for(true), Theme::plain());
+    env::remove_var("NO_COLOR");
+}
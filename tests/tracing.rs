@@ -0,0 +1,65 @@
+#![cfg(feature = "tracing")]
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use stringify::{Layout, Options, Stringifier, StringifyFields, Traced};
+use tracing::info;
+
+/// A writer that appends to a shared buffer.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Capture {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run `f` with a subscriber that formats fields with `fields`,
+/// and return what it wrote.
+fn capture<F>(fields: StringifyFields, f: F) -> String
+where F: FnOnce() {
+    let capture = Capture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .fmt_fields(fields)
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .with_level(false)
+        .with_target(false)
+        .without_time()
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    let bytes = capture.0.lock().unwrap().clone();
+    String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn traced_values_are_flat_without_stringify_fields() {
+    let values = vec![1u8, 2];
+    assert_eq!(format!("{:?}", Traced(&values)), "Vec [1, 2]");
+    assert_eq!(Traced(&values).to_string(), "Vec [1, 2]");
+}
+
+#[test]
+fn stringify_fields() {
+    let values = vec![1u8, 2];
+    let output = capture(StringifyFields::default(), || {
+        info!(values = ?Traced(&values), count = 2, name = "a", "loaded");
+    });
+    assert_eq!(output, "loaded values=Vec [\n    1,\n    2,\n] count=2 name=\"a\"\n");
+}
+
+#[test]
+fn stringify_fields_use_their_styles() {
+    let options = Options::default().with_layout(Layout::Compact);
+    let styles = Stringifier::default().with_options(options).styles().clone();
+    let values = vec![Some(1u8)];
+    let output = capture(StringifyFields::new(styles), || info!(values = ?Traced(&values)));
+    assert_eq!(output, "values=Vec [Some(1)]\n");
+}